@@ -1,37 +1,200 @@
 #![allow(unused)]
 
+pub mod bibtex;
+pub mod csl;
 pub mod db_items;
+pub mod identity;
+pub mod ris;
 pub mod xml_items;
 
 use std::{
+    collections::HashMap,
     fs,
     io::{self, Read},
+    path::Path,
     sync::OnceLock,
     time::Duration,
 };
 
 use chrono::naive::serde::ts_seconds_option;
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
 use regex::Regex;
 
 const DBLP_FILE: &str = "dblp.xml.gz";
+const DBLP_DTD_FILE: &str = "dblp.dtd";
 
 /// Matcher for XML references
 /// They follow this format:
 /// &xxxxx;
 const XML_REF_REGEX: &str = "&[[:alpha:]]+;";
 
-/// This method ingests the entire XML dataset and strips all
-/// references "$Agrage;" from it. This is performed before deserialization.
+/// The standard XML entities that must be left untouched, since the
+/// downstream quick-xml deserializer expects them.
+const XML_PREDEFINED_ENTITIES: &[&str] = &["amp", "lt", "gt", "quot", "apos"];
+
+/// Maps a DTD entity name (e.g. `auml`) to its declared replacement text.
+pub type EntityMap = HashMap<String, String>;
+
+static DTD_ENTITIES: OnceLock<EntityMap> = OnceLock::new();
+
+/// Lazily loads and caches the entity declarations from [DBLP_DTD_FILE].
+///
+/// Falls back to an empty map if the DTD cannot be found, so unresolvable
+/// references simply get stripped as before.
+pub(crate) fn dtd_entities() -> &'static EntityMap {
+    DTD_ENTITIES.get_or_init(|| load_entities_from_dtd(DBLP_DTD_FILE).unwrap_or_default())
+}
+
+/// Scans a DTD file's `<!ENTITY name "replacement">` declarations into an [EntityMap].
+pub fn load_entities_from_dtd(path: impl AsRef<Path>) -> io::Result<EntityMap> {
+    let dtd = fs::read_to_string(path)?;
+
+    Ok(parse_dtd_entities(&dtd))
+}
+
+/// Parses `<!ENTITY name "replacement">` declarations out of DTD source text.
+fn parse_dtd_entities(dtd: &str) -> EntityMap {
+    let regex =
+        Regex::new(r#"<!ENTITY\s+(\S+)\s+"([^"]*)"\s*>"#).expect("regex compilation must not fail");
+
+    regex
+        .captures_iter(dtd)
+        .map(|cap| (cap[1].to_string(), cap[2].to_string()))
+        .collect()
+}
+
+/// Substitutes every `&name;` reference in `input` with its declared replacement in
+/// `entities`. Standard XML entities (`&amp; &lt; &gt; &quot; &apos;`) are left intact.
+/// References with no DTD definition are stripped, matching the old behavior.
+pub fn resolve_references(input: &str, entities: &EntityMap) -> String {
+    let regex = Regex::new(XML_REF_REGEX).expect("regex compilation must not fail");
+
+    regex
+        .replace_all(input, |caps: &regex::Captures| {
+            let whole = &caps[0];
+            let name = whole.trim_start_matches('&').trim_end_matches(';');
+
+            if XML_PREDEFINED_ENTITIES.contains(&name) {
+                return whole.to_string();
+            }
+
+            entities.get(name).cloned().unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// This method ingests the entire XML dataset and resolves all references
+/// ("&xxxxx;") against the DBLP DTD, falling back to stripping them if
+/// undeclared. This is performed before deserialization.
 ///
 /// We will use the .dtd file to determine which references to strip.
 ///
 /// Gawddamn pesky
 pub fn strip_references(input_xml: &str) -> String {
-    let regex = Regex::new(XML_REF_REGEX).expect("regex compilation must not fail");
+    resolve_references(input_xml, dtd_entities())
+}
+
+/// Opens `path` as a buffered byte stream, transparently decompressing it if it
+/// ends in `.gz`. Used for constant-memory ingestion of multi-gigabyte DBLP dumps.
+pub fn open_xml_stream(path: impl AsRef<Path>) -> io::Result<Box<dyn io::BufRead>> {
+    let file = fs::File::open(path.as_ref())?;
+
+    match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+        Some("gz") => Ok(Box::new(io::BufReader::new(flate2::read::GzDecoder::new(
+            file,
+        )))),
+        _ => Ok(Box::new(io::BufReader::new(file))),
+    }
+}
+
+/// Streams level-1 XML elements out of `reader` one at a time, invoking `on_element`
+/// with each owned, entity-resolved element. Memory stays bounded by a single
+/// element's size regardless of the overall input size.
+pub fn stream_elements<R: io::BufRead>(
+    reader: R,
+    entities: &EntityMap,
+    mut on_element: impl FnMut(String),
+) -> io::Result<()> {
+    let mut xml_reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut inside_root = false;
+
+    loop {
+        let event = xml_reader
+            .read_event_into(&mut buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        match event {
+            Event::Eof => break,
+            // descend into the root element, so its children are what gets emitted
+            Event::Start(_) if !inside_root => inside_root = true,
+            Event::Start(start) => {
+                let element = read_element(&mut xml_reader, start.into_owned(), entities)?;
+                on_element(element);
+            }
+            _ => (),
+        }
 
-    let res = regex.replace_all(input_xml, "");
+        buf.clear();
+    }
 
-    res.into_owned()
+    Ok(())
+}
+
+/// Re-serializes a single level-1 element, from its opening `Event::Start` up to
+/// its matching `Event::End`, into an owned `String` with entity references resolved
+/// per `Event::Text` rather than over the whole document.
+fn read_element<R: io::BufRead>(
+    reader: &mut Reader<R>,
+    start: BytesStart<'static>,
+    entities: &EntityMap,
+) -> io::Result<String> {
+    use quick_xml::events::BytesText;
+
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    write_event(&mut writer, Event::Start(start))?;
+
+    let mut depth = 1;
+    let mut buf = Vec::new();
+
+    while depth != 0 {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        match event {
+            Event::Eof => break,
+            Event::Start(s) => {
+                depth += 1;
+                write_event(&mut writer, Event::Start(s.into_owned()))?;
+            }
+            Event::End(e) => {
+                depth -= 1;
+                write_event(&mut writer, Event::End(e.into_owned()))?;
+            }
+            Event::Text(t) => {
+                // resolve against the raw (still-escaped) text, same as `resolve_references`
+                let raw = String::from_utf8_lossy(t.as_ref()).into_owned();
+                let resolved = resolve_references(&raw, entities);
+                write_event(&mut writer, Event::Text(BytesText::from_escaped(resolved)))?;
+            }
+            other => write_event(&mut writer, other.into_owned())?,
+        }
+
+        buf.clear();
+    }
+
+    String::from_utf8(writer.into_inner())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_event(writer: &mut quick_xml::Writer<Vec<u8>>, event: Event<'static>) -> io::Result<()> {
+    writer
+        .write_event(event)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
 
 /// An XML viewer that reads the XML in chunks.
@@ -39,20 +202,20 @@ pub fn strip_references(input_xml: &str) -> String {
 ///
 /// Each chunk is guaranteed to be valid XML.
 ///
+/// Driven by a `quick_xml::Reader` rather than regexes, so comments, CDATA,
+/// processing instructions and `>` inside attribute values no longer confuse
+/// depth tracking.
+///
 /// XML tags reference: https://www.w3.org/TR/REC-xml/#sec-starttags
 #[derive(Debug)]
 pub struct ChunkedXmlViewer<'xml> {
-    offset: usize,
-    len: usize,
     num_chunks: usize,
 
     // copies of the root tag are needed
     root_tag_start: String,
     root_tag_end: String,
 
-    re_start: Regex,
-    re_end: Regex,
-    re_self_close: Regex,
+    reader: Reader<&'xml [u8]>,
 
     inner: &'xml str,
 }
@@ -66,44 +229,56 @@ impl Iterator for ChunkedXmlViewer<'_> {
 }
 
 impl<'xml> ChunkedXmlViewer<'xml> {
-    const XML_START_TAG: &'static str = r"<\w+>|<\w+";
-    const XML_END_TAG: &'static str = r"</\w+>|/>";
-    const XML_SELF_CLOSE_TAG: &'static str = r"<.*?/>";
-
     /// `num_chunks` specifies the number of level 1 XML elements to read in a single iteration.
     pub fn from_str(input: &'xml str, num_chunks: usize) -> Self {
-        // let start_tag;
-
-        let start_regex = Regex::new(Self::XML_START_TAG).expect("regex compilation must not fail");
-        let end_regex = Regex::new(Self::XML_END_TAG).expect("regex compilation must not fail");
-        let self_close_regex =
-            Regex::new(Self::XML_SELF_CLOSE_TAG).expect("regex compilation must not fail");
+        let mut root_reader = Reader::from_str(input);
+        let mut buf = Vec::new();
+
+        let root_start = loop {
+            match root_reader
+                .read_event_into(&mut buf)
+                .expect("no start tag found")
+            {
+                Event::Start(start) => break start.into_owned(),
+                Event::Eof => panic!("no start tag found"),
+                _ => buf.clear(),
+            }
+        };
 
-        let pos = start_regex.find(input).expect("no start tag found");
+        let root_name = String::from_utf8_lossy(root_start.name().as_ref()).into_owned();
+        let root_tag_start = Self::tag_text(&root_start);
+        let root_tag_end = format!("</{}>", root_name);
 
-        let tag_start = pos.as_str().to_owned();
-        let tag_end = format!("</{}>", pos.as_str().trim_matches(['<', '>']).to_string());
+        // straight to the first level 1 element
+        let inner = &input[root_reader.buffer_position() as usize..];
 
         Self {
-            offset: 0,
-            len: input.len() - pos.end(),
             num_chunks,
-            root_tag_start: tag_start,
-            root_tag_end: tag_end,
-            re_start: start_regex,
-            re_end: end_regex,
-            re_self_close: self_close_regex,
-
-            // straight to the first level 1 element
-            inner: &input[pos.end()..],
+            root_tag_start,
+            root_tag_end,
+            reader: Reader::from_str(inner),
+            inner,
         }
     }
 
-    pub fn next_chunk(&mut self) -> Option<String> {
-        if self.offset >= self.len {
-            return None;
-        }
+    /// Reconstructs a start tag (with its attributes) as it appeared in the source.
+    fn tag_text(start: &BytesStart) -> String {
+        let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+
+        let attrs: String = start
+            .attributes()
+            .filter_map(|attr| attr.ok())
+            .map(|attr| {
+                let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+                let value = String::from_utf8_lossy(attr.value.as_ref()).into_owned();
+                format!(" {}=\"{}\"", key, value)
+            })
+            .collect();
+
+        format!("<{}{}>", name, attrs)
+    }
 
+    pub fn next_chunk(&mut self) -> Option<String> {
         let mut count = self.num_chunks;
         let mut chunks = Vec::new();
 
@@ -128,137 +303,111 @@ impl<'xml> ChunkedXmlViewer<'xml> {
         }
     }
 
-    /// Returns the next element in the XML, without performing allocation.
+    /// Returns the next level-1 element in the XML, without performing allocation.
+    ///
+    /// `Event::Comment`/`Event::CData`/`Event::PI`/`Event::Decl`/`Event::Text` are ignored
+    /// for depth accounting: only `Event::Start`/`Event::End` change depth, and
+    /// `Event::Empty` is depth-neutral.
     pub fn next_element(&mut self) -> Option<&'xml str> {
-        let mut depth = 0;
-        let mut offset = 0;
-
-        // starting point
-        let reference = &self.inner[self.offset..];
-
-        // start by pushing the first starting tag
-        let start = self.re_start.find(reference)?;
-
-        depth += 1;
-        offset += start.end();
-
-        let mut reference = &reference[start.end()..];
-
-        while depth != 0 {
-            // println!("element depth: {}", depth);
-            println!("matching regexes...");
-            println!("remaining length: {}, peek: {}",reference.len(), &reference[..10]);
-
-            let start = self.re_start.find(reference).unwrap();
-            let end = self.re_end.find(reference).unwrap();
-            let self_close = self.re_self_close.find(reference);
-
-            // handle self closing tags
-            match self_close {
-                Some(s_close) => match (
-                    s_close.start().cmp(&start.start()),
-                    s_close.start().cmp(&end.start()),
-                ) {
-                    // self closing tags only affect the offset
-                    (std::cmp::Ordering::Less, std::cmp::Ordering::Less) => {
-                        println!("self closing tag: {}", s_close.as_str());
-
-                        // println!("self closing tag: {}", s_close.as_str());
-                        offset += s_close.end();
-                        reference = &reference[s_close.end()..];
-                        continue;
-                    }
-                    _ => (),
-                },
-                None => (),
-            }
+        let mut buf = Vec::new();
+        let start_pos = self.reader.buffer_position() as usize;
+
+        let mut depth: i32 = 0;
+        let mut started = false;
+
+        loop {
+            let event = match self.reader.read_event_into(&mut buf) {
+                Ok(Event::Eof) | Err(_) => return None,
+                Ok(event) => event,
+            };
 
-            // handle start and end tags
-            println!("handling start and end tags...");
-            match start.start().cmp(&end.start()) {
-                // start tag found
-                std::cmp::Ordering::Less => {
-                    // println!("opening tag: {}", start.as_str());
+            match event {
+                Event::Start(_) => {
                     depth += 1;
-                    offset += start.end();
-                    reference = &reference[start.end()..];
+                    started = true;
                 }
-                // end tag found
-                std::cmp::Ordering::Greater => {
-                    // println!("closing tag: {}", end.as_str());
+                Event::End(_) => {
                     depth -= 1;
-                    offset += end.end();
-                    reference = &reference[end.end()..];
+                    if started && depth == 0 {
+                        break;
+                    }
                 }
-                std::cmp::Ordering::Equal => {
-                    unimplemented!("both regex cannot match at the same position")
+                Event::Empty(_) if !started => {
+                    // a self-closing level-1 element is the whole chunk
+                    started = true;
+                    break;
                 }
+                // Comment, CData, PI, Decl, Text, and self-closing tags below
+                // the first level are depth-neutral.
+                _ => (),
             }
+
+            buf.clear();
         }
 
-        let res = Some(&self.inner[self.offset..(self.offset + offset)]);
-        self.offset += offset;
+        let end_pos = self.reader.buffer_position() as usize;
 
-        res
+        Some(&self.inner[start_pos..end_pos])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::db::{chunked_deserialize_insert, clear_tables, create_tables};
+    use crate::test_support::{sample_dblp_xml, temp_sqlite_path};
 
     use self::xml_items::RawDblp;
 
     use super::*;
 
-    #[test]
-    fn test_match_regex() {
-        let re_start = Regex::new(ChunkedXmlViewer::XML_START_TAG).unwrap();
-        let re_end = Regex::new(ChunkedXmlViewer::XML_END_TAG).unwrap();
-        let re_self_close = Regex::new(ChunkedXmlViewer::XML_SELF_CLOSE_TAG).unwrap();
-
-        let start_tags = &["<open asd='123'>", "<open>"];
-        let end_tags = &["</close>", "/>"];
-        let self_close_tags = &["<self_close/>", "<self_close asd='123'/>"];
-
-        for tag in start_tags {
-            assert!(re_start.is_match(tag));
-        }
+    /// The fixture's entity declarations, standing in for `dblp.dtd` so the
+    /// test doesn't depend on it being present.
+    fn fixture_entities() -> EntityMap {
+        HashMap::from([("uuml".to_string(), "ü".to_string())])
+    }
 
-        for tag in end_tags {
-            assert!(re_end.is_match(tag));
-        }
+    #[test]
+    fn test_resolve_references_entities() {
+        let resolved = resolve_references("Stefan M&uuml;ller &amp; friends", &fixture_entities());
 
-        for tag in self_close_tags {
-            assert!(re_self_close.is_match(tag));
-        }
+        assert_eq!(resolved, "Stefan Müller &amp; friends");
     }
 
     #[test]
     fn test_chunk_viewer() {
-        let xml_file = fs::read_to_string("dblp.xml").unwrap();
+        let xml = sample_dblp_xml();
 
-        let mut viewer = ChunkedXmlViewer::from_str(&xml_file, 10);
+        let mut viewer = ChunkedXmlViewer::from_str(xml, 10);
+        let mut count = 0;
 
-        while let Some(elem) = viewer.next_element() {
-            println!("{}", elem);
+        while let Some(_elem) = viewer.next_element() {
+            count += 1;
         }
 
-        // for chunk in viewer {
-        //     let raw_data: RawDblp = quick_xml::de::from_str(&chunk).unwrap();
-        // }
+        // one level-1 element per <article>/<inproceedings>/<www> in the fixture
+        assert_eq!(count, 3);
     }
 
     #[test]
     fn test_chunked_write_to_db() {
-        let xml_file = fs::read_to_string("dblp.xml").unwrap();
-        let filtered = strip_references(&xml_file);
+        let filtered = resolve_references(sample_dblp_xml(), &fixture_entities());
 
-        let mut conn = rusqlite::Connection::open("temp.sqlite").unwrap();
+        let (_dir, db_path) = temp_sqlite_path();
+        let mut conn = rusqlite::Connection::open(&db_path).unwrap();
         create_tables(&conn).unwrap();
         clear_tables(&conn).unwrap();
         create_tables(&conn).unwrap();
 
         chunked_deserialize_insert(&mut conn, &filtered).unwrap();
+
+        let publication_count: u32 = conn
+            .query_row("SELECT COUNT(*) FROM publications", (), |r| r.get(0))
+            .unwrap();
+        assert_eq!(publication_count, 2);
+
+        let person_count: u32 = conn
+            .query_row("SELECT COUNT(*) FROM persons", (), |r| r.get(0))
+            .unwrap();
+        assert_eq!(person_count, 1);
     }
 }
@@ -0,0 +1,594 @@
+//! BibTeX/BibLaTeX round-trip between `.bib` text and [RawDblp].
+//!
+//! [from_bibtex] hand-parses `@entrytype{citekey, field = {value}, ...}`
+//! entries (brace- and quote-delimited values, `@string` abbreviations,
+//! `#`-concatenation, nested braces) into [RawDblp]. [ToBibtex] serializes
+//! the other way, mirroring [super::ris]'s `ToRis`.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use super::identity::split_name;
+use super::xml_items::{
+    Article, Author, Book, InCollection, InProceeding, MastersThesis, PhdThesis, Proceeding,
+    PublicationRecord, RawDblp,
+};
+
+/// Errors produced while parsing a `.bib` document.
+#[derive(Debug, Error)]
+pub enum BibtexError {
+    #[error("unexpected end of input while parsing {0}")]
+    UnexpectedEof(&'static str),
+    #[error("expected `{expected}` at byte {pos}, found `{found}`")]
+    Expected {
+        expected: char,
+        found: char,
+        pos: usize,
+    },
+    #[error("unknown entry type `@{0}`")]
+    UnknownEntryType(String),
+    #[error("undefined @string abbreviation `{0}`")]
+    UndefinedString(String),
+}
+
+/// Renders a parsed DBLP record as a BibTeX entry.
+pub trait ToBibtex {
+    fn to_bibtex(&self) -> String;
+}
+
+/// One raw `@entrytype{citekey, field = value, ...}` entry, before it's
+/// mapped onto a [RawDblp] record type.
+struct RawEntry {
+    entry_type: String,
+    citekey: String,
+    fields: HashMap<String, String>,
+}
+
+/// A cursor over the input bytes, tracking a byte position for error
+/// messages.
+struct Cursor<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn advance(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), BibtexError> {
+        match self.advance() {
+            Some(c) if c == expected as u8 => Ok(()),
+            Some(c) => Err(BibtexError::Expected {
+                expected,
+                found: c as char,
+                pos: self.pos - 1,
+            }),
+            None => Err(BibtexError::UnexpectedEof("expected character")),
+        }
+    }
+
+    /// Reads an identifier: entry types, citekeys, and field names are all
+    /// runs of non-whitespace, non-`{}(),=#` characters.
+    fn read_identifier(&mut self) -> &'a str {
+        let start = self.pos;
+
+        while let Some(c) = self.peek() {
+            if c.is_ascii_whitespace()
+                || matches!(c, b'{' | b'}' | b'(' | b')' | b',' | b'=' | b'#')
+            {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        std::str::from_utf8(&self.input[start..self.pos]).unwrap_or_default()
+    }
+
+    /// Reads a `{...}`-delimited value, honoring nested braces (used to
+    /// protect capitalization) as literal text.
+    fn read_braced_value(&mut self) -> Result<String, BibtexError> {
+        self.expect('{')?;
+
+        let mut depth = 1u32;
+        let start = self.pos;
+
+        while depth > 0 {
+            match self.advance() {
+                Some(b'{') => depth += 1,
+                Some(b'}') => depth -= 1,
+                Some(_) => (),
+                None => return Err(BibtexError::UnexpectedEof("braced value")),
+            }
+        }
+
+        let end = self.pos - 1; // exclude the closing brace just consumed
+        Ok(String::from_utf8_lossy(&self.input[start..end]).into_owned())
+    }
+
+    /// Reads a `"..."`-delimited value. A `"` nested inside a brace group
+    /// doesn't end the value - only one at brace-depth `0` does.
+    fn read_quoted_value(&mut self) -> Result<String, BibtexError> {
+        self.expect('"')?;
+
+        let mut depth = 0u32;
+        let start = self.pos;
+
+        loop {
+            match self.advance() {
+                Some(b'{') => depth += 1,
+                Some(b'}') => depth = depth.saturating_sub(1),
+                Some(b'"') if depth == 0 => break,
+                Some(_) => (),
+                None => return Err(BibtexError::UnexpectedEof("quoted value")),
+            }
+        }
+
+        let end = self.pos - 1; // exclude the closing quote just consumed
+        Ok(String::from_utf8_lossy(&self.input[start..end]).into_owned())
+    }
+
+    /// Reads one field value: a brace/quote-delimited literal, or a bare
+    /// `@string` abbreviation, optionally `#`-concatenated with more of the
+    /// same.
+    fn read_value(&mut self, strings: &HashMap<String, String>) -> Result<String, BibtexError> {
+        let mut value = String::new();
+
+        loop {
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b'{') => value.push_str(&self.read_braced_value()?),
+                Some(b'"') => value.push_str(&self.read_quoted_value()?),
+                Some(_) => {
+                    let ident = self.read_identifier();
+                    if ident.is_empty() {
+                        return Err(BibtexError::UnexpectedEof("field value"));
+                    }
+
+                    // bare numbers (e.g. `year = 2011`) stand for themselves,
+                    // anything else is an @string abbreviation reference.
+                    if ident.chars().all(|c| c.is_ascii_digit()) {
+                        value.push_str(ident);
+                    } else {
+                        let resolved = strings
+                            .get(ident)
+                            .ok_or_else(|| BibtexError::UndefinedString(ident.to_string()))?;
+                        value.push_str(resolved);
+                    }
+                }
+                None => return Err(BibtexError::UnexpectedEof("field value")),
+            }
+
+            self.skip_whitespace();
+            if self.peek() == Some(b'#') {
+                self.pos += 1;
+                continue;
+            }
+
+            break;
+        }
+
+        Ok(value)
+    }
+
+    /// Advances to (and consumes) the next `@`, returning `false` at EOF.
+    fn skip_to_next_entry(&mut self) -> bool {
+        while let Some(c) = self.peek() {
+            if c == b'@' {
+                self.pos += 1;
+                return true;
+            }
+            self.pos += 1;
+        }
+
+        false
+    }
+}
+
+/// Parses every `@string`/`@entrytype{...}` in `input`, resolving `@string`
+/// abbreviations as they're defined (definition must precede use, as in real
+/// BibTeX) and returning the non-`@string` entries in source order.
+fn parse_entries(input: &str) -> Result<Vec<RawEntry>, BibtexError> {
+    let mut cursor = Cursor::new(input);
+    let mut strings = HashMap::new();
+    let mut entries = Vec::new();
+
+    while cursor.skip_to_next_entry() {
+        let entry_type = cursor.read_identifier().to_ascii_lowercase();
+        cursor.skip_whitespace();
+        cursor.expect('{')?;
+
+        if entry_type == "comment" || entry_type == "preamble" {
+            // skip the brace-delimited body; its contents aren't a field list
+            cursor.pos -= 1; // un-consume the `{` so read_braced_value can re-read it
+            cursor.read_braced_value()?;
+            continue;
+        }
+
+        cursor.skip_whitespace();
+
+        if entry_type == "string" {
+            let name = cursor.read_identifier().to_string();
+            cursor.skip_whitespace();
+            cursor.expect('=')?;
+            let value = cursor.read_value(&strings)?;
+            strings.insert(name, value);
+            cursor.skip_whitespace();
+            cursor.expect('}')?;
+            continue;
+        }
+
+        let citekey = cursor.read_identifier().to_string();
+        cursor.skip_whitespace();
+
+        let mut fields = HashMap::new();
+        loop {
+            if cursor.peek() == Some(b',') {
+                cursor.pos += 1;
+                cursor.skip_whitespace();
+            }
+
+            if cursor.peek() == Some(b'}') {
+                cursor.pos += 1;
+                break;
+            }
+
+            let field_name = cursor.read_identifier().to_ascii_lowercase();
+            cursor.skip_whitespace();
+            cursor.expect('=')?;
+            let value = cursor.read_value(&strings)?;
+            fields.insert(field_name, value);
+            cursor.skip_whitespace();
+        }
+
+        entries.push(RawEntry {
+            entry_type,
+            citekey,
+            fields,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Converts `"Last, First"` (BibTeX's preferred author form) back into
+/// DBLP's `"First Last"` form. Names without a comma are passed through
+/// unchanged.
+fn unswap_name(name: &str) -> String {
+    match name.split_once(", ") {
+        Some((surname, given)) => format!("{} {}", given.trim(), surname.trim()),
+        None => name.trim().to_string(),
+    }
+}
+
+fn authors_from_field(field: Option<&String>) -> Vec<Author> {
+    field
+        .map(|authors| {
+            authors
+                .split(" and ")
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .map(|a| Author {
+                    name: unswap_name(a),
+                    aux: None,
+                    bibtex: None,
+                    orcid: None,
+                    label: None,
+                    given_name: None,
+                    surname: None,
+                    canonical_id: None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn publication_record_from_entry(entry: &RawEntry) -> PublicationRecord {
+    PublicationRecord {
+        key: entry.citekey.clone(),
+        mdate: None,
+        publtype: None,
+        year: entry.fields.get("year").and_then(|y| y.trim().parse().ok()),
+        authors: authors_from_field(entry.fields.get("author")),
+        title: entry.fields.get("title").cloned(),
+        booktitle: entry.fields.get("booktitle").cloned(),
+        crossref: None,
+        relation: None,
+        school: entry.fields.get("school").cloned().into_iter().collect(),
+        publisher: entry.fields.get("publisher").cloned().into_iter().collect(),
+        citations: Vec::new(),
+    }
+}
+
+/// Parses `input` (the contents of a `.bib` file) into a [RawDblp].
+///
+/// Entry types are mapped onto DBLP's record wrappers the same way
+/// [ToBibtex] maps them back: `article`/`inproceedings`/`proceedings`/
+/// `book`/`incollection`/`phdthesis`/`mastersthesis`. Unrecognized entry
+/// types (e.g. `@collection`, `@misc`, which have no DBLP wrapper) are
+/// reported as [BibtexError::UnknownEntryType] rather than silently dropped.
+pub fn from_bibtex(input: &str) -> Result<RawDblp, BibtexError> {
+    let mut dblp = RawDblp {
+        articles: Vec::new(),
+        inproceedings: Vec::new(),
+        proceedings: Vec::new(),
+        books: Vec::new(),
+        incollections: Vec::new(),
+        phd_theses: Vec::new(),
+        masters_theses: Vec::new(),
+        data: Vec::new(),
+        web_pages: Vec::new(),
+        mdate: None,
+    };
+
+    for entry in parse_entries(input)? {
+        let record = publication_record_from_entry(&entry);
+
+        match entry.entry_type.as_str() {
+            "article" => dblp.articles.push(Article(record)),
+            "inproceedings" => dblp.inproceedings.push(InProceeding(record)),
+            "proceedings" => dblp.proceedings.push(Proceeding(record)),
+            "book" => dblp.books.push(Book(record)),
+            "incollection" => dblp.incollections.push(InCollection(record)),
+            "phdthesis" => dblp.phd_theses.push(PhdThesis(record)),
+            "mastersthesis" => dblp.masters_theses.push(MastersThesis(record)),
+            other => return Err(BibtexError::UnknownEntryType(other.to_string())),
+        }
+    }
+
+    Ok(dblp)
+}
+
+/// Escapes BibTeX's special characters (`{}%&_`) by prefixing them with a
+/// backslash.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        if matches!(c, '{' | '}' | '%' | '&' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Renders `"First Last"` into BibTeX's preferred `"Last, First"` form, via
+/// [super::identity::split_name] so BibTeX agrees with [super::ris]/
+/// [super::csl]'s author-name splitting instead of duplicating (and
+/// disagreeing with) it.
+fn swap_name(name: &str) -> String {
+    match split_name(name) {
+        (Some(given), Some(surname)) => format!("{}, {}", surname, given),
+        (None, Some(surname)) => surname,
+        _ => name.to_string(),
+    }
+}
+
+/// Shared rendering for every [PublicationRecord]-wrapping newtype, given its
+/// BibTeX entry type.
+fn render_publication(record: &PublicationRecord, entry_type: &str) -> String {
+    let mut fields = Vec::new();
+
+    if let Some(title) = record.title.as_deref().filter(|t| !t.is_empty()) {
+        fields.push(format!("  title = {{{}}}", escape(title)));
+    }
+
+    if let Some(booktitle) = record.booktitle.as_deref().filter(|b| !b.is_empty()) {
+        fields.push(format!("  booktitle = {{{}}}", escape(booktitle)));
+    }
+
+    if !record.authors.is_empty() {
+        let authors = record
+            .authors
+            .iter()
+            .map(|a| swap_name(&a.name))
+            .collect::<Vec<_>>()
+            .join(" and ");
+        fields.push(format!("  author = {{{}}}", escape(&authors)));
+    }
+
+    if let Some(year) = record.year {
+        fields.push(format!("  year = {{{}}}", year));
+    }
+
+    if let Some(publisher) = record.publisher.first().filter(|p| !p.is_empty()) {
+        fields.push(format!("  publisher = {{{}}}", escape(publisher)));
+    }
+
+    if let Some(school) = record.school.first().filter(|s| !s.is_empty()) {
+        fields.push(format!("  school = {{{}}}", escape(school)));
+    }
+
+    format!(
+        "@{}{{{},\n{}\n}}",
+        entry_type,
+        record.key,
+        fields.join(",\n")
+    )
+}
+
+macro_rules! impl_to_bibtex {
+    ($ty: ty, $entry_type: expr) => {
+        impl ToBibtex for $ty {
+            fn to_bibtex(&self) -> String {
+                render_publication(&self.0, $entry_type)
+            }
+        }
+    };
+}
+
+impl_to_bibtex! {Article, "article"}
+impl_to_bibtex! {InProceeding, "inproceedings"}
+impl_to_bibtex! {Proceeding, "proceedings"}
+impl_to_bibtex! {Book, "book"}
+impl_to_bibtex! {InCollection, "incollection"}
+impl_to_bibtex! {PhdThesis, "phdthesis"}
+impl_to_bibtex! {MastersThesis, "mastersthesis"}
+
+/// Renders every record in `dblp` that has a BibTeX entry-type mapping,
+/// joined with a blank line between entries. `data`/`web_pages` have no
+/// natural BibTeX entry type and are skipped.
+pub fn write_bibtex(dblp: &RawDblp) -> String {
+    let mut blocks = Vec::new();
+
+    blocks.extend(dblp.articles.iter().map(ToBibtex::to_bibtex));
+    blocks.extend(dblp.inproceedings.iter().map(ToBibtex::to_bibtex));
+    blocks.extend(dblp.proceedings.iter().map(ToBibtex::to_bibtex));
+    blocks.extend(dblp.books.iter().map(ToBibtex::to_bibtex));
+    blocks.extend(dblp.incollections.iter().map(ToBibtex::to_bibtex));
+    blocks.extend(dblp.phd_theses.iter().map(ToBibtex::to_bibtex));
+    blocks.extend(dblp.masters_theses.iter().map(ToBibtex::to_bibtex));
+
+    blocks.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_article() -> Article {
+        Article(PublicationRecord {
+            key: "journals/test/Mueller23".to_string(),
+            mdate: None,
+            publtype: None,
+            year: Some(2023),
+            authors: vec![
+                Author {
+                    name: "Stefan Mueller".to_string(),
+                    aux: None,
+                    bibtex: None,
+                    orcid: None,
+                    label: None,
+                    given_name: None,
+                    surname: None,
+                    canonical_id: None,
+                },
+                Author {
+                    name: "Jane Doe".to_string(),
+                    aux: None,
+                    bibtex: None,
+                    orcid: None,
+                    label: None,
+                    given_name: None,
+                    surname: None,
+                    canonical_id: None,
+                },
+            ],
+            title: Some("On Graphs and Networks".to_string()),
+            booktitle: None,
+            crossref: None,
+            relation: None,
+            school: Vec::new(),
+            publisher: vec!["Test Press".to_string()],
+            citations: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_to_bibtex_from_bibtex_round_trip() {
+        let article = sample_article();
+        let rendered = article.to_bibtex();
+
+        let parsed = from_bibtex(&rendered).unwrap();
+        assert_eq!(parsed.articles.len(), 1);
+
+        let record = &parsed.articles[0].0;
+        assert_eq!(record.key, article.0.key);
+        assert_eq!(record.year, article.0.year);
+        assert_eq!(record.title, article.0.title);
+        assert_eq!(record.publisher, article.0.publisher);
+        assert_eq!(
+            record.authors.iter().map(|a| &a.name).collect::<Vec<_>>(),
+            article
+                .0
+                .authors
+                .iter()
+                .map(|a| &a.name)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_from_bibtex_string_and_concatenation() {
+        let bib = r#"
+@string{iclr = "International Conference on Learning Representations"}
+
+@inproceedings{conf/test/Doe22,
+  title = {A Study of {Nested Braces} and} # { Concatenation},
+  booktitle = iclr,
+  author = {Doe, Jane and Public, John},
+  year = {2022},
+}
+"#;
+
+        let dblp = from_bibtex(bib).unwrap();
+        assert_eq!(dblp.inproceedings.len(), 1);
+
+        let record = &dblp.inproceedings[0].0;
+        assert_eq!(record.key, "conf/test/Doe22");
+        assert_eq!(
+            record.title.as_deref(),
+            Some("A Study of {Nested Braces} and Concatenation")
+        );
+        assert_eq!(
+            record.booktitle.as_deref(),
+            Some("International Conference on Learning Representations")
+        );
+        assert_eq!(record.year, Some(2022));
+        assert_eq!(
+            record
+                .authors
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Jane Doe", "John Public"]
+        );
+    }
+
+    #[test]
+    fn test_from_bibtex_unknown_entry_type() {
+        let bib = "@misc{foo/bar, title = {Untyped}}";
+        assert!(matches!(
+            from_bibtex(bib),
+            Err(BibtexError::UnknownEntryType(t)) if t == "misc"
+        ));
+    }
+
+    #[test]
+    fn test_swap_name_given_and_surname() {
+        assert_eq!(swap_name("Jane Doe"), "Doe, Jane");
+    }
+
+    #[test]
+    fn test_swap_name_keeps_homonym_disambiguator_attached() {
+        assert_eq!(swap_name("John Smith 0002"), "Smith 0002, John");
+    }
+
+    #[test]
+    fn test_swap_name_single_token() {
+        assert_eq!(swap_name("Madonna"), "Madonna");
+    }
+}
@@ -0,0 +1,104 @@
+//! CSL-JSON (Citation Style Language) export for parsed DBLP records.
+//!
+//! CSL-JSON is the JSON interchange format citeproc engines (Zotero,
+//! Pandoc, ...) consume directly: an array of items, each typed by CSL's
+//! own vocabulary rather than ours. Mirrors [super::ris]/[super::bibtex]'s
+//! per-wrapper-type trait + batch-writer shape.
+
+use serde_json::{json, Value};
+
+use super::identity::split_name;
+use super::xml_items::{
+    Article, Book, Data, InCollection, InProceeding, MastersThesis, PhdThesis, Proceeding,
+    PublicationRecord, RawDblp,
+};
+
+/// Renders a parsed DBLP record as a CSL-JSON item.
+pub trait ToCslJson {
+    fn to_csl_json(&self) -> Value;
+}
+
+/// Builds a CSL `{given, family}` author object from a DBLP author name,
+/// rounding the ORCID through as an `orcid` key when present.
+fn author_object(name: &str, orcid: Option<&str>) -> Value {
+    let (given, family) = split_name(name);
+
+    let mut author = match (given, family) {
+        (Some(given), Some(family)) => json!({ "given": given, "family": family }),
+        (None, Some(family)) => json!({ "family": family }),
+        _ => json!({ "family": name }),
+    };
+
+    if let Some(orcid) = orcid {
+        author["orcid"] = Value::String(orcid.to_string());
+    }
+
+    author
+}
+
+/// Shared rendering for every [PublicationRecord]-wrapping newtype, given its
+/// CSL item type.
+fn render_publication(record: &PublicationRecord, csl_type: &str) -> Value {
+    let authors = record
+        .authors
+        .iter()
+        .map(|author| author_object(&author.name, author.orcid.as_deref()))
+        .collect::<Vec<_>>();
+
+    let mut item = json!({
+        "id": record.key,
+        "type": csl_type,
+        "author": authors,
+    });
+
+    if let Some(title) = record.title.as_deref().filter(|t| !t.is_empty()) {
+        item["title"] = Value::String(title.to_string());
+    }
+
+    if let Some(year) = record.year {
+        item["issued"] = json!({ "date-parts": [[year]] });
+    }
+
+    if let Some(publisher) = record.publisher.first().filter(|p| !p.is_empty()) {
+        item["publisher"] = Value::String(publisher.clone());
+    }
+
+    item
+}
+
+macro_rules! impl_to_csl_json {
+    ($ty: ty, $csl_type: expr) => {
+        impl ToCslJson for $ty {
+            fn to_csl_json(&self) -> Value {
+                render_publication(&self.0, $csl_type)
+            }
+        }
+    };
+}
+
+impl_to_csl_json! {Article, "article-journal"}
+impl_to_csl_json! {InProceeding, "paper-conference"}
+impl_to_csl_json! {Proceeding, "paper-conference"}
+impl_to_csl_json! {Book, "book"}
+impl_to_csl_json! {InCollection, "chapter"}
+impl_to_csl_json! {PhdThesis, "thesis"}
+impl_to_csl_json! {MastersThesis, "thesis"}
+impl_to_csl_json! {Data, "dataset"}
+
+/// Serializes every record in `dblp` that has a CSL item-type mapping as a
+/// CSL-JSON array. `web_pages` have no natural CSL item type and are
+/// skipped.
+pub fn write_csl_json(dblp: &RawDblp) -> Value {
+    let mut items = Vec::new();
+
+    items.extend(dblp.articles.iter().map(ToCslJson::to_csl_json));
+    items.extend(dblp.inproceedings.iter().map(ToCslJson::to_csl_json));
+    items.extend(dblp.proceedings.iter().map(ToCslJson::to_csl_json));
+    items.extend(dblp.books.iter().map(ToCslJson::to_csl_json));
+    items.extend(dblp.incollections.iter().map(ToCslJson::to_csl_json));
+    items.extend(dblp.phd_theses.iter().map(ToCslJson::to_csl_json));
+    items.extend(dblp.masters_theses.iter().map(ToCslJson::to_csl_json));
+    items.extend(dblp.data.iter().map(ToCslJson::to_csl_json));
+
+    Value::Array(items)
+}
@@ -3,9 +3,12 @@
 //! Basically, the data types defined in this module are filtered versions of
 //! the ones in [super::data_items].
 
-use std::{borrow::Borrow, collections::HashSet, fmt::Display, str::FromStr};
+use std::{collections::HashSet, fmt::Display, str::FromStr};
 
-use pyo3::{exceptions::PyTypeError, pyclass, pymethods, PyRef, PyRefMut, PyResult};
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    pyclass, pymethods, PyRef, PyRefMut, PyResult,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{db, get_init_conn_pool};
@@ -37,6 +40,9 @@ pub struct DblpRecord {
     #[pyo3(get)]
     pub year: Option<u32>,
 
+    #[pyo3(get)]
+    pub title: Option<String>,
+
     /// Authors are referenced by their profile.
     // #[pyo3(get)]
     pub authors: Option<String>,
@@ -127,17 +133,6 @@ struct AssociatedAuthorYear {
     authors: String,
 }
 
-impl<'a, R: Borrow<rusqlite::Row<'a>>> From<R> for AssociatedAuthorYear {
-    fn from(value: R) -> Self {
-        let row = value.borrow();
-
-        Self {
-            year: row.get(0).unwrap(),
-            authors: row.get(1).unwrap(),
-        }
-    }
-}
-
 #[pymethods]
 impl DblpRecord {
     pub fn __iter__(slf: PyRef<'_, Self>) -> DblpRecordIter {
@@ -162,6 +157,119 @@ impl DblpRecord {
                 .collect(),
         )
     }
+
+    /// Renders this record in the RIS interchange format, for import into
+    /// reference managers.
+    ///
+    /// `None`/empty fields are skipped entirely. The `::`-joined `authors`
+    /// field becomes one `AU` line per author, and each citation key becomes
+    /// its own `N1` note line.
+    pub fn to_ris(&self) -> String {
+        let mut lines = vec![format!("TY  - {}", self.record.ris_type())];
+
+        if let Some(title) = self.title.as_deref().filter(|t| !t.is_empty()) {
+            lines.push(format!("TI  - {}", title));
+        }
+
+        if let Some(authors) = self.authors() {
+            lines.extend(authors.iter().map(|author| format!("AU  - {}", author)));
+        }
+
+        if let Some(year) = self.year {
+            lines.push(format!("PY  - {}", year));
+        }
+
+        if let Some(publisher) = self.publisher.as_deref().filter(|p| !p.is_empty()) {
+            lines.push(format!("PB  - {}", publisher));
+        }
+
+        lines.push(format!("ID  - {}", self.key));
+
+        if let Some(citations) = self.citations.as_deref().filter(|c| !c.is_empty()) {
+            lines.extend(
+                citations
+                    .trim_matches(SEPARATOR.chars().collect::<Vec<_>>().as_slice())
+                    .split(SEPARATOR)
+                    .filter(|c| !c.is_empty())
+                    .map(|citation| format!("N1  - {}", citation)),
+            );
+        }
+
+        lines.push("ER  - ".to_string());
+
+        lines.join("\n")
+    }
+
+    /// Renders this record as a BibTeX/BibLaTeX entry, using the DBLP `key`
+    /// as the cite key.
+    ///
+    /// `title`, `year`, `publisher`, and `school` fields are only emitted
+    /// when present, and `authors()` is joined with `" and "` into a single
+    /// `author` field.
+    pub fn to_bibtex(&self) -> String {
+        let mut fields = Vec::new();
+
+        if let Some(title) = self.title.as_deref().filter(|t| !t.is_empty()) {
+            fields.push(format!("  title = {{{}}}", title));
+        }
+
+        if let Some(authors) = self.authors().filter(|a| !a.is_empty()) {
+            fields.push(format!("  author = {{{}}}", authors.join(" and ")));
+        }
+
+        if let Some(year) = self.year {
+            fields.push(format!("  year = {{{}}}", year));
+        }
+
+        if let Some(publisher) = self.publisher.as_deref().filter(|p| !p.is_empty()) {
+            fields.push(format!("  publisher = {{{}}}", publisher));
+        }
+
+        if let Some(school) = self.school.as_deref().filter(|s| !s.is_empty()) {
+            fields.push(format!("  school = {{{}}}", school));
+        }
+
+        format!(
+            "@{}{{{},\n{}\n}}",
+            self.record.bibtex_type(),
+            self.key,
+            fields.join(",\n")
+        )
+    }
+
+    /// Serializes this record as a CSL-JSON item (the format citeproc/Zotero
+    /// consume), returned as a JSON string.
+    ///
+    /// Each `::`-joined author name is split on its last space into a
+    /// `{ "family", "given" }` object, `issued.date-parts` comes from `year`,
+    /// and `id`/`type` come from `key`/[PublicationRecord::csl_type].
+    pub fn to_csl_json(&self) -> PyResult<String> {
+        let authors = self
+            .authors()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|name| match name.rsplit_once(' ') {
+                Some((given, family)) => serde_json::json!({ "given": given, "family": family }),
+                None => serde_json::json!({ "family": name }),
+            })
+            .collect::<Vec<_>>();
+
+        let mut item = serde_json::json!({
+            "id": self.key,
+            "type": self.record.csl_type(),
+            "author": authors,
+        });
+
+        if let Some(title) = self.title.as_deref().filter(|t| !t.is_empty()) {
+            item["title"] = serde_json::Value::String(title.to_string());
+        }
+
+        if let Some(year) = self.year {
+            item["issued"] = serde_json::json!({ "date-parts": [[year]] });
+        }
+
+        serde_json::to_string(&item).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
 }
 
 #[pymethods]
@@ -182,24 +290,14 @@ impl PersonRecord {
         let conn = get_init_conn_pool();
 
         // optim step: select only the authors field and collect into a set
-        let publications =
-            db::raw_publications_query(&conn, format!("WHERE authors like '%::{}::%'", self.name))
-                .map_err(|e| PyTypeError::new_err(e.to_string()))?;
-
-        // println!("publications found: {}", publications.len());
+        let query = crate::query_builder::CoauthorQuery::new().author(self.name.clone());
+        let rows =
+            db::query_coauthors(&conn, &query).map_err(|e| PyTypeError::new_err(e.to_string()))?;
 
-        let co_auth_names = publications
+        let co_auth_names = rows
             .iter()
-            .map(|p| {
-                p.authors
-                    .as_ref()
-                    .and_then(|a| Some(a.as_str()))
-                    .unwrap_or("")
-                    .trim_end_matches(SEPARATOR)
-                    .split(SEPARATOR)
-                    .map(|name| name)
-            })
-            .flatten()
+            .filter_map(|(_, authors)| authors.as_deref())
+            .flat_map(|a| a.trim_end_matches(SEPARATOR).split(SEPARATOR))
             .collect::<HashSet<_>>();
 
         Ok(co_auth_names
@@ -215,6 +313,10 @@ impl PersonRecord {
 impl PersonRecord {
     /// Construct the temporal relations of the person with their coauthors.
     ///
+    /// `constraints` restricts each year's coauthor set to names also present
+    /// in `constraints` - the names of the other persons the caller is
+    /// building a relation graph over, not every coauthor on record.
+    ///
     /// This is the only way to construct a [PersonTemporalRelation].
     ///
     /// TODO: optimize to one query, then do post-processing
@@ -222,6 +324,7 @@ impl PersonRecord {
         &self,
         start: u32,
         end: u32,
+        constraints: &HashSet<String>,
     ) -> rusqlite::Result<PersonTemporalRelation> {
         let mut relation = PersonTemporalRelation {
             author: self.name.to_string(),
@@ -230,22 +333,20 @@ impl PersonRecord {
         };
         let conn = get_init_conn_pool();
 
-        let mut stmt = conn.prepare(&format!(
-            "
-            SELECT publications.year, publications.authors
-            FROM persons
-            JOIN publications ON publications.authors LIKE '%::' || persons.name  || '::%'
-            WHERE publications.year >= ? AND publications.year <= ?
-            AND persons.id = ?
-            ORDER BY publications.year ASC
-        "
-        ))?;
-
-        let rows = stmt.query_map(rusqlite::params![start, end, self.id], |r| {
-            Ok(AssociatedAuthorYear::from(r))
-        })?;
-
-        let a = rows.filter_map(|r| r.ok()).collect::<Vec<_>>();
+        let query = crate::query_builder::CoauthorQuery::new()
+            .author_id(self.id)
+            .year_range(start, end);
+        let rows = db::query_coauthors(&conn, &query)?;
+
+        let a = rows
+            .into_iter()
+            .filter_map(|(year, authors)| {
+                Some(AssociatedAuthorYear {
+                    year: year?,
+                    authors: authors?,
+                })
+            })
+            .collect::<Vec<_>>();
         let mut co_authors = HashSet::new();
 
         for yr in start..=end {
@@ -253,7 +354,8 @@ impl PersonRecord {
 
             for assoc in a.iter().filter(|a| a.year == yr) {
                 co_auth.extend(
-                    assoc.authors
+                    assoc
+                        .authors
                         .trim_end_matches(SEPARATOR)
                         .split(SEPARATOR)
                         .map(|c| c.to_string())
@@ -263,7 +365,16 @@ impl PersonRecord {
 
             co_authors.extend(co_auth);
             co_authors.remove(&self.name); // remove self from coauthors
-            relation.coauthor_years.push(co_authors.clone());
+
+            // restrict to the persons the caller is actually building a
+            // relation graph over, rather than every coauthor on record
+            relation.coauthor_years.push(
+                co_authors
+                    .iter()
+                    .filter(|c| constraints.contains(*c))
+                    .cloned()
+                    .collect(),
+            );
         }
 
         // inclusive range
@@ -322,17 +433,21 @@ impl DblpRecordIter {
             }
             5 => {
                 slf.field += 1;
-                Some(("authors".to_string(), slf.inner.authors.clone()))
+                Some(("title".to_string(), slf.inner.title.clone()))
             }
             6 => {
                 slf.field += 1;
-                Some(("citations".to_string(), slf.inner.citations.clone()))
+                Some(("authors".to_string(), slf.inner.authors.clone()))
             }
             7 => {
                 slf.field += 1;
-                Some(("publisher".to_string(), slf.inner.publisher.clone()))
+                Some(("citations".to_string(), slf.inner.citations.clone()))
             }
             8 => {
+                slf.field += 1;
+                Some(("publisher".to_string(), slf.inner.publisher.clone()))
+            }
+            9 => {
                 slf.field += 1;
                 Some(("school".to_string(), slf.inner.school.clone()))
             }
@@ -437,6 +552,7 @@ macro_rules! try_into_dblp_record {
                     mdate: value.0.mdate.and_then(|d| Some(d.to_string())),
                     publtype: value.0.publtype,
                     year: value.0.year,
+                    title: value.0.title,
                     authors: {
                         let val = value
                         .0
@@ -582,6 +698,50 @@ impl From<RawDblp> for (Vec<DblpRecord>, Vec<PersonRecord>) {
     }
 }
 
+impl PublicationRecord {
+    /// RIS type tag for this variant, per the RIS interchange spec.
+    fn ris_type(&self) -> &'static str {
+        match self {
+            PublicationRecord::Article => "JOUR",
+            PublicationRecord::InProceeding => "CPAPER",
+            PublicationRecord::Proceeding => "CONF",
+            PublicationRecord::Book => "BOOK",
+            PublicationRecord::InCollection => "CHAP",
+            PublicationRecord::Collection => "EDBOOK",
+            PublicationRecord::PhdThesis | PublicationRecord::MastersThesis => "THES",
+            PublicationRecord::Data => "DATA",
+        }
+    }
+
+    /// BibTeX/BibLaTeX entry type for this variant.
+    fn bibtex_type(&self) -> &'static str {
+        match self {
+            PublicationRecord::Article => "article",
+            PublicationRecord::InProceeding => "inproceedings",
+            PublicationRecord::Proceeding => "proceedings",
+            PublicationRecord::Book => "book",
+            PublicationRecord::InCollection => "incollection",
+            PublicationRecord::Collection => "collection",
+            PublicationRecord::PhdThesis => "phdthesis",
+            PublicationRecord::MastersThesis => "mastersthesis",
+            PublicationRecord::Data => "misc",
+        }
+    }
+
+    /// CSL item type for this variant, per the Citation Style Language
+    /// schema.
+    fn csl_type(&self) -> &'static str {
+        match self {
+            PublicationRecord::Article => "article-journal",
+            PublicationRecord::InProceeding | PublicationRecord::Proceeding => "paper-conference",
+            PublicationRecord::Book | PublicationRecord::Collection => "book",
+            PublicationRecord::InCollection => "chapter",
+            PublicationRecord::PhdThesis | PublicationRecord::MastersThesis => "thesis",
+            PublicationRecord::Data => "dataset",
+        }
+    }
+}
+
 impl Display for PublicationRecord {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,286 @@
+//! Author identity resolution: splitting [Author::name] into given/surname,
+//! and unifying alias spellings of the same person behind a canonical id.
+//!
+//! The same person can appear under several alias spellings in DBLP (a
+//! "Home Page" [WebPage] record's `author` list is every alias they've
+//! published under), which makes raw-name coauthorship graphs undercount.
+//! [resolve_identities]/[canonicalize_authors] unify those under one id: the
+//! author's ORCID when present, otherwise the DBLP profile path
+//! ([WebPage::key]) of a matching alias, otherwise the raw name (no
+//! unification found).
+
+use std::collections::{HashMap, HashSet};
+
+use super::xml_items::{Author, RawDblp, WebPage};
+
+/// Splits `name` into `(given_name, surname)` on the last whitespace token.
+/// A trailing 4-digit DBLP homonym disambiguator (e.g. the `"0002"` in
+/// `"John Smith 0002"`) is kept attached to the surname rather than treated
+/// as its own token.
+pub fn split_name(name: &str) -> (Option<String>, Option<String>) {
+    let is_disambiguator =
+        |token: &str| token.len() == 4 && token.bytes().all(|b| b.is_ascii_digit());
+
+    let (rest, disambiguator) = match name.rsplit_once(' ') {
+        Some((rest, last)) if is_disambiguator(last) => (rest, Some(last)),
+        _ => (name, None),
+    };
+
+    match rest.rsplit_once(' ') {
+        Some((given, surname)) => (
+            Some(given.to_string()),
+            Some(attach(surname, disambiguator)),
+        ),
+        None if !rest.is_empty() => (None, Some(attach(rest, disambiguator))),
+        None => (None, None),
+    }
+}
+
+fn attach(surname: &str, disambiguator: Option<&str>) -> String {
+    match disambiguator {
+        Some(d) => format!("{} {}", surname, d),
+        None => surname.to_string(),
+    }
+}
+
+/// Populates [Author::given_name]/[Author::surname] from [Author::name].
+pub fn populate_name_parts(author: &mut Author) {
+    let (given_name, surname) = split_name(&author.name);
+    author.given_name = given_name;
+    author.surname = surname;
+}
+
+/// Builds the `alias name -> profile path` map from every [WebPage] in
+/// `dblp` - a "Home Page" record's `author` list is every alias spelling
+/// its subject has published under, and `key` is their stable profile path.
+fn alias_map(web_pages: &[WebPage]) -> HashMap<&str, &str> {
+    let mut aliases = HashMap::new();
+
+    for page in web_pages {
+        for alias in &page.author {
+            aliases.insert(alias.as_str(), page.key.as_str());
+        }
+    }
+
+    aliases
+}
+
+/// Resolves a single author to a canonical id: their ORCID when present
+/// (`"orcid:<id>"`), else the profile path of a matching alias, else their
+/// raw name.
+fn canonical_id(author: &Author, aliases: &HashMap<&str, &str>) -> String {
+    if let Some(orcid) = &author.orcid {
+        return format!("orcid:{}", orcid);
+    }
+
+    if let Some(profile) = aliases.get(author.name.as_str()) {
+        return profile.to_string();
+    }
+
+    author.name.clone()
+}
+
+/// Runs every [Author] (across every publication-record vector in `dblp`)
+/// through [canonical_id], returning the canonical id -> set-of-name-variants
+/// map described in the identity-resolution design.
+pub fn resolve_identities(dblp: &RawDblp) -> HashMap<String, HashSet<String>> {
+    let aliases = alias_map(&dblp.web_pages);
+    let mut variants: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for author in all_authors(dblp) {
+        let id = canonical_id(author, &aliases);
+        variants.entry(id).or_default().insert(author.name.clone());
+    }
+
+    variants
+}
+
+fn all_authors(dblp: &RawDblp) -> impl Iterator<Item = &Author> {
+    dblp.articles
+        .iter()
+        .map(|r| &r.0.authors)
+        .chain(dblp.inproceedings.iter().map(|r| &r.0.authors))
+        .chain(dblp.proceedings.iter().map(|r| &r.0.authors))
+        .chain(dblp.books.iter().map(|r| &r.0.authors))
+        .chain(dblp.incollections.iter().map(|r| &r.0.authors))
+        .chain(dblp.phd_theses.iter().map(|r| &r.0.authors))
+        .chain(dblp.masters_theses.iter().map(|r| &r.0.authors))
+        .chain(dblp.data.iter().map(|r| &r.0.authors))
+        .flatten()
+}
+
+fn all_authors_mut(dblp: &mut RawDblp) -> impl Iterator<Item = &mut Author> {
+    dblp.articles
+        .iter_mut()
+        .map(|r| &mut r.0.authors)
+        .chain(dblp.inproceedings.iter_mut().map(|r| &mut r.0.authors))
+        .chain(dblp.proceedings.iter_mut().map(|r| &mut r.0.authors))
+        .chain(dblp.books.iter_mut().map(|r| &mut r.0.authors))
+        .chain(dblp.incollections.iter_mut().map(|r| &mut r.0.authors))
+        .chain(dblp.phd_theses.iter_mut().map(|r| &mut r.0.authors))
+        .chain(dblp.masters_theses.iter_mut().map(|r| &mut r.0.authors))
+        .chain(dblp.data.iter_mut().map(|r| &mut r.0.authors))
+        .flatten()
+}
+
+/// Mutates every [Author] in `dblp` in place: populates `given_name`/
+/// `surname` ([populate_name_parts]) and sets `canonical_id` to the id
+/// [resolve_identities] would assign it, so downstream coauthorship graphs
+/// can group by `canonical_id` instead of raw name and collapse aliases.
+pub fn canonicalize_authors(dblp: &mut RawDblp) {
+    let aliases: HashMap<String, String> = alias_map(&dblp.web_pages)
+        .into_iter()
+        .map(|(alias, profile)| (alias.to_string(), profile.to_string()))
+        .collect();
+
+    for author in all_authors_mut(dblp) {
+        populate_name_parts(author);
+
+        author.canonical_id = Some(match &author.orcid {
+            Some(orcid) => format!("orcid:{}", orcid),
+            None => aliases
+                .get(author.name.as_str())
+                .cloned()
+                .unwrap_or_else(|| author.name.clone()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::xml_items::Article;
+    use super::*;
+    use crate::dataset::xml_items::PublicationRecord;
+
+    fn author(name: &str, orcid: Option<&str>) -> Author {
+        Author {
+            name: name.to_string(),
+            aux: None,
+            bibtex: None,
+            orcid: orcid.map(str::to_string),
+            label: None,
+            given_name: None,
+            surname: None,
+            canonical_id: None,
+        }
+    }
+
+    fn web_page(key: &str, aliases: &[&str]) -> WebPage {
+        WebPage {
+            key: key.to_string(),
+            title: vec!["Home Page".to_string()],
+            url: Vec::new(),
+            author: aliases.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    fn empty_dblp() -> RawDblp {
+        RawDblp {
+            articles: Vec::new(),
+            inproceedings: Vec::new(),
+            proceedings: Vec::new(),
+            books: Vec::new(),
+            incollections: Vec::new(),
+            phd_theses: Vec::new(),
+            masters_theses: Vec::new(),
+            data: Vec::new(),
+            web_pages: Vec::new(),
+            mdate: None,
+        }
+    }
+
+    fn article(authors: Vec<Author>) -> Article {
+        Article(PublicationRecord {
+            key: "journals/test/Foo23".to_string(),
+            mdate: None,
+            publtype: None,
+            year: Some(2023),
+            authors,
+            title: None,
+            booktitle: None,
+            crossref: None,
+            relation: None,
+            school: Vec::new(),
+            publisher: Vec::new(),
+            citations: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_split_name_empty() {
+        assert_eq!(split_name(""), (None, None));
+    }
+
+    #[test]
+    fn test_split_name_single_token() {
+        assert_eq!(split_name("Madonna"), (None, Some("Madonna".to_string())));
+    }
+
+    #[test]
+    fn test_split_name_given_and_surname() {
+        assert_eq!(
+            split_name("Jane Doe"),
+            (Some("Jane".to_string()), Some("Doe".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_split_name_keeps_homonym_disambiguator_attached() {
+        assert_eq!(
+            split_name("John Smith 0002"),
+            (Some("John".to_string()), Some("Smith 0002".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_split_name_trailing_non_disambiguator_token_is_surname() {
+        assert_eq!(
+            split_name("Jane Doe Jr"),
+            (Some("Jane Doe".to_string()), Some("Jr".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_authors_orcid_present() {
+        let mut dblp = empty_dblp();
+        dblp.articles.push(article(vec![author(
+            "Jane Doe",
+            Some("0000-0001-2345-6789"),
+        )]));
+
+        canonicalize_authors(&mut dblp);
+
+        assert_eq!(
+            dblp.articles[0].0.authors[0].canonical_id.as_deref(),
+            Some("orcid:0000-0001-2345-6789")
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_authors_alias_match() {
+        let mut dblp = empty_dblp();
+        dblp.web_pages
+            .push(web_page("homepages/12/Doe", &["J. Doe", "Jane Doe"]));
+        dblp.articles.push(article(vec![author("J. Doe", None)]));
+
+        canonicalize_authors(&mut dblp);
+
+        assert_eq!(
+            dblp.articles[0].0.authors[0].canonical_id.as_deref(),
+            Some("homepages/12/Doe")
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_authors_no_match_falls_back_to_name() {
+        let mut dblp = empty_dblp();
+        dblp.articles.push(article(vec![author("Jane Doe", None)]));
+
+        canonicalize_authors(&mut dblp);
+
+        assert_eq!(
+            dblp.articles[0].0.authors[0].canonical_id.as_deref(),
+            Some("Jane Doe")
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! RIS (Research Information Systems) export for parsed DBLP records.
+//!
+//! RIS is a line-oriented tagged format: each line is a two-letter tag, two
+//! spaces, a hyphen, a space, then the value; every reference starts with
+//! `TY  - <type>` and ends with `ER  - `.
+
+use super::identity::split_name;
+use super::xml_items::{
+    Article, Book, Data, InCollection, InProceeding, MastersThesis, PhdThesis, Proceeding,
+    PublicationRecord, RawDblp, WebPage,
+};
+
+/// Renders a parsed DBLP record as an RIS tagged-line reference.
+pub trait ToRis {
+    fn to_ris(&self) -> String;
+}
+
+/// Renders `name` in RIS's `Surname, Given` form, via
+/// [super::identity::split_name] so RIS agrees with [super::csl]'s
+/// author-name splitting instead of duplicating (and disagreeing with) it.
+fn ris_name(name: &str) -> String {
+    match split_name(name) {
+        (Some(given), Some(surname)) => format!("{}, {}", surname, given),
+        (None, Some(surname)) => surname,
+        _ => name.to_string(),
+    }
+}
+
+/// Shared rendering for every [PublicationRecord]-wrapping newtype, given its
+/// RIS type tag.
+fn render_publication(record: &PublicationRecord, ris_type: &str) -> String {
+    let mut lines = vec![format!("TY  - {}", ris_type)];
+
+    if let Some(title) = record.title.as_deref().filter(|t| !t.is_empty()) {
+        lines.push(format!("TI  - {}", title));
+    }
+
+    if let Some(booktitle) = record.booktitle.as_deref().filter(|b| !b.is_empty()) {
+        lines.push(format!("BT  - {}", booktitle));
+    }
+
+    lines.extend(
+        record
+            .authors
+            .iter()
+            .map(|author| format!("AU  - {}", ris_name(&author.name))),
+    );
+
+    if let Some(year) = record.year {
+        lines.push(format!("PY  - {}", year));
+    }
+
+    lines.extend(
+        record
+            .publisher
+            .iter()
+            .map(|publisher| format!("PB  - {}", publisher)),
+    );
+
+    if let Some(mdate) = record.mdate {
+        lines.push(format!("DA  - {}", mdate));
+    }
+
+    lines.push(format!("ID  - {}", record.key));
+    lines.push("ER  - ".to_string());
+
+    lines.join("\n")
+}
+
+macro_rules! impl_to_ris {
+    ($ty: ty, $ris_type: expr) => {
+        impl ToRis for $ty {
+            fn to_ris(&self) -> String {
+                render_publication(&self.0, $ris_type)
+            }
+        }
+    };
+}
+
+impl_to_ris! {Article, "JOUR"}
+impl_to_ris! {InProceeding, "CPAPER"}
+impl_to_ris! {Proceeding, "CONF"}
+impl_to_ris! {Book, "BOOK"}
+impl_to_ris! {InCollection, "CHAP"}
+impl_to_ris! {PhdThesis, "THES"}
+impl_to_ris! {MastersThesis, "THES"}
+impl_to_ris! {Data, "DATA"}
+
+impl ToRis for WebPage {
+    fn to_ris(&self) -> String {
+        let mut lines = vec!["TY  - ELEC".to_string()];
+
+        if let Some(title) = self.title.first() {
+            lines.push(format!("TI  - {}", title));
+        }
+
+        lines.extend(
+            self.author
+                .iter()
+                .map(|author| format!("AU  - {}", ris_name(author))),
+        );
+
+        lines.extend(self.url.iter().map(|url| format!("UR  - {}", url)));
+
+        lines.push(format!("ID  - {}", self.key));
+        lines.push("ER  - ".to_string());
+
+        lines.join("\n")
+    }
+}
+
+/// Renders every record in `dblp` as RIS, one reference per record, joined
+/// with a blank line between references.
+pub fn write_ris(dblp: &RawDblp) -> String {
+    let mut blocks = Vec::new();
+
+    blocks.extend(dblp.articles.iter().map(ToRis::to_ris));
+    blocks.extend(dblp.inproceedings.iter().map(ToRis::to_ris));
+    blocks.extend(dblp.proceedings.iter().map(ToRis::to_ris));
+    blocks.extend(dblp.books.iter().map(ToRis::to_ris));
+    blocks.extend(dblp.incollections.iter().map(ToRis::to_ris));
+    blocks.extend(dblp.phd_theses.iter().map(ToRis::to_ris));
+    blocks.extend(dblp.masters_theses.iter().map(ToRis::to_ris));
+    blocks.extend(dblp.data.iter().map(ToRis::to_ris));
+    blocks.extend(dblp.web_pages.iter().map(ToRis::to_ris));
+
+    blocks.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::xml_items::Author;
+    use super::*;
+
+    fn author(name: &str) -> Author {
+        Author {
+            name: name.to_string(),
+            aux: None,
+            bibtex: None,
+            orcid: None,
+            label: None,
+            given_name: None,
+            surname: None,
+            canonical_id: None,
+        }
+    }
+
+    #[test]
+    fn test_ris_name_given_and_surname() {
+        assert_eq!(ris_name("Jane Doe"), "Doe, Jane");
+    }
+
+    #[test]
+    fn test_ris_name_keeps_homonym_disambiguator_attached() {
+        assert_eq!(ris_name("John Smith 0002"), "Smith 0002, John");
+    }
+
+    #[test]
+    fn test_ris_name_single_token() {
+        assert_eq!(ris_name("Madonna"), "Madonna");
+    }
+
+    #[test]
+    fn test_write_ris_article() {
+        let article = Article(PublicationRecord {
+            key: "journals/test/Mueller23".to_string(),
+            mdate: None,
+            publtype: None,
+            year: Some(2023),
+            authors: vec![author("Stefan Mueller"), author("Jane Doe")],
+            title: Some("On Graphs and Networks".to_string()),
+            booktitle: None,
+            crossref: None,
+            relation: None,
+            school: Vec::new(),
+            publisher: vec!["Test Press".to_string()],
+            citations: Vec::new(),
+        });
+
+        let rendered = article.to_ris();
+        let lines = rendered.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "TY  - JOUR");
+        assert!(lines.contains(&"TI  - On Graphs and Networks"));
+        assert!(lines.contains(&"AU  - Mueller, Stefan"));
+        assert!(lines.contains(&"AU  - Doe, Jane"));
+        assert!(lines.contains(&"PY  - 2023"));
+        assert!(lines.contains(&"PB  - Test Press"));
+        assert_eq!(lines.last(), Some(&"ER  - "));
+    }
+}
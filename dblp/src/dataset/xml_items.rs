@@ -2,6 +2,8 @@
 //!
 //! XML schema description found here: https://dblp.org/faq/16154937.html
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Raw data derserialized from the DBLP `xml` dataset.
@@ -46,6 +48,95 @@ pub struct RawDblp {
     pub mdate: Option<chrono::NaiveDate>,
 }
 
+impl RawDblp {
+    /// Every [PublicationRecord] in `self`, across every publication-record
+    /// vector ([WebPage] has no underlying [PublicationRecord]).
+    fn records(&self) -> impl Iterator<Item = &PublicationRecord> {
+        self.articles
+            .iter()
+            .map(|r| &r.0)
+            .chain(self.inproceedings.iter().map(|r| &r.0))
+            .chain(self.proceedings.iter().map(|r| &r.0))
+            .chain(self.books.iter().map(|r| &r.0))
+            .chain(self.incollections.iter().map(|r| &r.0))
+            .chain(self.phd_theses.iter().map(|r| &r.0))
+            .chain(self.masters_theses.iter().map(|r| &r.0))
+            .chain(self.data.iter().map(|r| &r.0))
+    }
+
+    fn records_mut(&mut self) -> impl Iterator<Item = &mut PublicationRecord> {
+        self.articles
+            .iter_mut()
+            .map(|r| &mut r.0)
+            .chain(self.inproceedings.iter_mut().map(|r| &mut r.0))
+            .chain(self.proceedings.iter_mut().map(|r| &mut r.0))
+            .chain(self.books.iter_mut().map(|r| &mut r.0))
+            .chain(self.incollections.iter_mut().map(|r| &mut r.0))
+            .chain(self.phd_theses.iter_mut().map(|r| &mut r.0))
+            .chain(self.masters_theses.iter_mut().map(|r| &mut r.0))
+            .chain(self.data.iter_mut().map(|r| &mut r.0))
+    }
+
+    /// Indexes every [PublicationRecord] in `self` by [PublicationRecord::key].
+    fn index(&self) -> HashMap<&str, &PublicationRecord> {
+        self.records()
+            .map(|record| (record.key.as_str(), record))
+            .collect()
+    }
+
+    /// Looks up the [PublicationRecord] with this key, across every
+    /// publication-record vector.
+    pub fn parent_of(&self, key: &str) -> Option<&PublicationRecord> {
+        self.index().get(key).copied()
+    }
+
+    /// Resolves every record's [PublicationRecord::crossref] by inheriting
+    /// `year`/`publisher`/`booktitle` from the referenced parent wherever
+    /// the child is missing it. Returns the `crossref` keys that didn't
+    /// match any record in `self`.
+    pub fn resolve_crossrefs(&mut self) -> Vec<String> {
+        let inheritable: HashMap<String, (Option<u32>, Vec<String>, Option<String>)> = self
+            .index()
+            .into_iter()
+            .map(|(key, record)| {
+                (
+                    key.to_string(),
+                    (
+                        record.year,
+                        record.publisher.clone(),
+                        record.booktitle.clone(),
+                    ),
+                )
+            })
+            .collect();
+
+        let mut unresolved = Vec::new();
+
+        for record in self.records_mut() {
+            let Some(crossref) = record.crossref.clone() else {
+                continue;
+            };
+
+            match inheritable.get(&crossref) {
+                Some((year, publisher, booktitle)) => {
+                    if record.year.is_none() {
+                        record.year = *year;
+                    }
+                    if record.publisher.is_empty() {
+                        record.publisher = publisher.clone();
+                    }
+                    if record.booktitle.is_none() {
+                        record.booktitle = booktitle.clone();
+                    }
+                }
+                None => unresolved.push(crossref),
+            }
+        }
+
+        unresolved
+    }
+}
+
 /// Common internal representation of a publication record.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PublicationRecord {
@@ -70,6 +161,17 @@ pub struct PublicationRecord {
     #[serde(default)]
     pub authors: Vec<Author>,
 
+    /// Title of the publication
+    pub title: Option<String>,
+
+    /// Title of the book/proceedings a chapter or paper appears in
+    pub booktitle: Option<String>,
+
+    /// Key of the parent record (e.g. a `proceedings`/`book`) this record
+    /// belongs to. Missing `year`/`publisher`/`booktitle` can often be
+    /// inherited from it via [RawDblp::resolve_crossrefs].
+    pub crossref: Option<String>,
+
     /// Relation to other records
     #[serde(rename = "rel")]
     pub relation: Option<Relation>,
@@ -133,6 +235,24 @@ pub struct Author {
     pub orcid: Option<String>,
     #[serde(rename = "@label")]
     pub label: Option<String>,
+
+    /// Given name, derived from [Author::name] by
+    /// [super::identity::populate_name_parts]. Not present in the source XML.
+    #[serde(skip)]
+    pub given_name: Option<String>,
+
+    /// Surname, derived from [Author::name] by
+    /// [super::identity::populate_name_parts]. A trailing 4-digit DBLP
+    /// homonym disambiguator (e.g. the `"0002"` in `"John Smith 0002"`)
+    /// stays attached to this field rather than being split off on its own.
+    #[serde(skip)]
+    pub surname: Option<String>,
+
+    /// Canonical author id, assigned by
+    /// [super::identity::canonicalize_authors] to collapse alias spellings
+    /// of the same person. Not present in the source XML.
+    #[serde(skip)]
+    pub canonical_id: Option<String>,
 }
 
 /// Any related items to the publication record.
@@ -2,19 +2,143 @@
 //!
 //! That includes all (most) SQL queries.
 
-use std::{borrow::Borrow, collections::HashSet, io::Write, str::FromStr, sync::mpsc};
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{Connection, ToSql};
+use regex::Regex;
+use rusqlite::{
+    functions::{Context, FunctionFlags},
+    Connection, OptionalExtension, ToSql,
+};
 
 use crate::dataset::db_items::{DblpRecord, PersonRecord, PublicationRecord, SEPARATOR};
 
 // type DbConnectionPool = Pool<SqliteConnectionManager>;
 type DbConnection = PooledConnection<SqliteConnectionManager>;
 
+/// Pragmas applied to every connection a pool hands out.
+///
+/// The default enables WAL + `synchronous=NORMAL` and a 5 second busy timeout, since
+/// `create_subset_database` spins up multiple writer threads sharing one `Pool`, and
+/// the default rollback-journal mode plus a zero busy-timeout means concurrent
+/// writers hit `SQLITE_BUSY` immediately.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionOptions {
+    pub enable_foreign_keys: bool,
+    pub busy_timeout: Option<Duration>,
+    pub wal: bool,
+    pub synchronous_normal: bool,
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        Self {
+            enable_foreign_keys: false,
+            busy_timeout: Some(Duration::from_secs(5)),
+            wal: true,
+            synchronous_normal: true,
+        }
+    }
+}
+
+impl ConnectionOptions {
+    /// Applies these options to `conn` via `PRAGMA`s.
+    fn apply(&self, conn: &Connection) -> rusqlite::Result<()> {
+        if self.wal {
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+        }
+
+        if self.synchronous_normal {
+            conn.pragma_update(None, "synchronous", "NORMAL")?;
+        }
+
+        if let Some(timeout) = self.busy_timeout {
+            conn.busy_timeout(timeout)?;
+        }
+
+        if self.enable_foreign_keys {
+            conn.pragma_update(None, "foreign_keys", "ON")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a connection manager that applies `options` to every connection the
+/// pool hands out, via `SqliteConnectionManager::with_init`.
+pub fn connection_manager(
+    path: impl AsRef<Path>,
+    options: ConnectionOptions,
+) -> SqliteConnectionManager {
+    SqliteConnectionManager::file(path).with_init(move |conn| options.apply(conn))
+}
+
+/// Registers a `regexp(pattern, text)` scalar function on `conn`, which SQLite wires
+/// up to the `text REGEXP pattern` operator. Lets `query_author_regex` and
+/// `query_publications_regex` express matches (e.g. "John Doe" with or without a
+/// trailing serial) that chained `LIKE` clauses can't.
+///
+/// Invalid patterns return `false` rather than erroring the whole query. The
+/// compiled `Regex` is cached in the function's auxiliary data, keyed on the
+/// pattern argument, so repeated rows don't recompile it.
+pub fn register_regexp_function(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx: &Context| {
+            let regex: Arc<Regex> = match ctx.get_aux::<Regex>(0)? {
+                Some(regex) => regex,
+                None => {
+                    let pattern = match ctx.get::<String>(0) {
+                        Ok(p) => p,
+                        Err(_) => return Ok(false),
+                    };
+
+                    let regex = match Regex::new(&pattern) {
+                        Ok(regex) => Arc::new(regex),
+                        Err(_) => return Ok(false),
+                    };
+
+                    ctx.set_aux(0, regex.clone());
+                    regex
+                }
+            };
+
+            let text = match ctx.get::<String>(1) {
+                Ok(text) => text,
+                Err(_) => return Ok(false),
+            };
+
+            Ok(regex.is_match(&text))
+        },
+    )
+}
+
 /// Checks if the database contains the necessary tables, and that they have stuff in them.
+///
+/// Also brings the schema up to date via [crate::migrations::run_migrations],
+/// reporting the version it upgraded from if the on-disk database was behind.
 pub fn check_database(conn: &Connection) -> rusqlite::Result<()> {
+    let before = crate::migrations::schema_version(conn)?;
+    crate::migrations::run_migrations(conn)?;
+
+    if before.on_disk != before.latest {
+        println!(
+            "upgraded database schema from version {} to {}",
+            before.on_disk, before.latest
+        );
+    }
+
     let mut stmt = conn.prepare("SELECT COUNT(name) from persons;")?;
     let _ = stmt.query(())?;
 
@@ -28,35 +152,13 @@ pub fn check_database(conn: &Connection) -> rusqlite::Result<()> {
     Ok(())
 }
 
-/// Initializes the database tables and drops all indexes.
+/// Initializes the database tables (and any schema added since). Existing
+/// indexes are left as they are.
+///
+/// Delegates to [crate::migrations::run_migrations] so a freshly created
+/// database ends up on the exact same schema as an upgraded one.
 pub fn create_tables(conn: &DbConnection) -> rusqlite::Result<()> {
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS persons(
-            id INTEGER PRIMARY KEY,
-            name TEXT NOT NULL,
-            profile TEXT NOT NULL,
-            aliases TEXT NOT NULL
-        )",
-        (),
-    )?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS publications(
-            id INTEGER PRIMARY KEY,
-            record TEXT NOT NULL,
-            key TEXT NOT NULL,
-            mdate TEXT,
-            publtype TEXT,
-            year INTEGER,
-            authors TEXT,
-            citations TEXT,
-            publisher TEXT,
-            school TEXT
-        )",
-        (),
-    )?;
-
-    Ok(())
+    crate::migrations::run_migrations(conn)
 }
 
 fn create_all_indexes(conn: &DbConnection) -> rusqlite::Result<()> {
@@ -117,6 +219,7 @@ impl<'a, R: Borrow<rusqlite::Row<'a>>> From<R> for DblpRecord {
             citations: row.get(7).ok(),
             publisher: row.get(8).ok(),
             school: row.get(9).ok(),
+            title: row.get(10).ok(),
         }
     }
 }
@@ -135,18 +238,105 @@ impl<'a, R: Borrow<rusqlite::Row<'a>>> From<R> for PersonRecord {
     }
 }
 
-/// Drops the tables in the database.
+/// Drops the tables in the database, and resets `user_version` back to `0` so
+/// a later [create_tables] replays every migration from scratch instead of
+/// finding the database already "up to date" with nothing left to create.
 pub fn clear_tables(conn: &DbConnection) -> rusqlite::Result<()> {
     // let c = conn.get().unwrap();
 
     conn.execute("DROP TABLE IF EXISTS persons", ())?;
     conn.execute("DROP TABLE IF EXISTS publications", ())?;
+    conn.execute("DROP TABLE IF EXISTS person_name_tokens", ())?;
+    conn.execute("DROP TABLE IF EXISTS publication_key_tokens", ())?;
+    conn.execute("DROP TABLE IF EXISTS publication_title_tokens", ())?;
+    conn.execute("DROP TABLE IF EXISTS authorship", ())?;
+    conn.pragma_update(None, "user_version", 0)?;
 
     drop_all_indexes(&conn)?;
     Ok(())
 }
 
-/// Inserts the given records into the database.
+/// Inserts one publication row, alongside its entries in
+/// `publication_key_tokens`/`publication_title_tokens` ([crate::fuzzy]) and
+/// `authorship`. Shared by [dump_into_database] and [update_from_xml_stream].
+///
+/// Each author is resolved against `persons.name` via `person_lookup_stmt`,
+/// so `authorship.person_id` can back an indexed equi-join instead of the
+/// `authors LIKE '%::name::%'` scan it replaces - left `NULL` for authors with
+/// no matching `persons` row. Callers must insert persons before
+/// publications within the same chunk/transaction, so authors introduced in
+/// this very chunk are still resolvable.
+fn insert_publication_row(
+    tx: &rusqlite::Transaction,
+    stmt: &mut rusqlite::CachedStatement,
+    token_stmt: &mut rusqlite::CachedStatement,
+    title_token_stmt: &mut rusqlite::CachedStatement,
+    authorship_stmt: &mut rusqlite::CachedStatement,
+    person_lookup_stmt: &mut rusqlite::CachedStatement,
+    publication: &DblpRecord,
+) -> rusqlite::Result<()> {
+    stmt.execute((
+        publication.record.to_string(),
+        publication.key.to_owned(),
+        publication.mdate.to_owned(),
+        publication.publtype.to_owned(),
+        publication.year.to_owned(),
+        publication.authors.to_owned(),
+        publication.citations.to_owned(),
+        publication.publisher.to_owned(),
+        publication.school.to_owned(),
+        publication.title.to_owned(),
+    ))?;
+
+    let publication_id = tx.last_insert_rowid();
+    for token in crate::fuzzy::tokenize(&publication.key) {
+        token_stmt.execute((token, publication_id))?;
+    }
+
+    let title_or_key = publication.title.as_deref().unwrap_or(&publication.key);
+    for token in crate::fuzzy::tokenize(title_or_key) {
+        title_token_stmt.execute((token, publication_id))?;
+    }
+
+    if let Some(authors) = publication.authors() {
+        for author in authors {
+            let person_id: Option<i64> = person_lookup_stmt
+                .query_row((&author,), |r| r.get(0))
+                .optional()?;
+
+            authorship_stmt.execute((publication_id, &author, person_id, publication.year))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Inserts one person row, alongside its entries in `person_name_tokens`
+/// ([crate::fuzzy]). Shared by [dump_into_database] and
+/// [update_from_xml_stream].
+fn insert_person_row(
+    tx: &rusqlite::Transaction,
+    stmt: &mut rusqlite::CachedStatement,
+    token_stmt: &mut rusqlite::CachedStatement,
+    person: &PersonRecord,
+) -> rusqlite::Result<()> {
+    stmt.execute((
+        person.name.to_owned(),
+        person.profile.to_owned(),
+        person.aliases.to_owned(),
+    ))?;
+
+    let person_id = tx.last_insert_rowid();
+    for token in crate::fuzzy::tokenize(&person.name) {
+        token_stmt.execute((token, person_id))?;
+    }
+
+    Ok(())
+}
+
+/// Inserts the given records into the database, alongside their entries in
+/// the token-prefix indexes ([crate::fuzzy]) that fuzzy search narrows
+/// candidates against.
 pub fn dump_into_database(
     conn: &mut DbConnection,
     records: &[DblpRecord],
@@ -154,43 +344,70 @@ pub fn dump_into_database(
 ) -> rusqlite::Result<()> {
     let tx = conn.transaction()?;
 
-    let mut stmt = tx.prepare(
-        "INSERT INTO publications
-    (record, key, mdate, publtype, year, authors, citations, publisher, school)
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-    )?;
+    // persons are inserted first so `insert_publication_row`'s
+    // `person_lookup_stmt` can resolve authors introduced in this same batch.
+    {
+        let mut stmt =
+            tx.prepare_cached("INSERT INTO persons (name, profile, aliases) VALUES (?, ?, ?)")?;
+        let mut token_stmt =
+            tx.prepare_cached("INSERT INTO person_name_tokens (token, person_id) VALUES (?, ?)")?;
 
-    for publication in records.iter() {
-        stmt.execute((
-            publication.record.to_string(),
-            publication.key.to_owned(),
-            publication.mdate.to_owned(),
-            publication.publtype.to_owned(),
-            publication.year.to_owned(),
-            publication.authors.to_owned(),
-            publication.citations.to_owned(),
-            publication.publisher.to_owned(),
-            publication.school.to_owned(),
-        ))?;
+        for person in persons.iter() {
+            insert_person_row(&tx, &mut stmt, &mut token_stmt, person)?;
+        }
     }
-    drop(stmt);
 
-    let mut stmt = tx.prepare("INSERT INTO persons (name, profile, aliases) VALUES (?, ?, ?)")?;
-
-    for person in persons.iter() {
-        stmt.execute((
-            person.name.to_owned(),
-            person.profile.to_owned(),
-            person.aliases.to_owned(),
-        ))?;
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO publications
+        (record, key, mdate, publtype, year, authors, citations, publisher, school, title)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        let mut token_stmt = tx.prepare_cached(
+            "INSERT INTO publication_key_tokens (token, publication_id) VALUES (?, ?)",
+        )?;
+        let mut title_token_stmt = tx.prepare_cached(
+            "INSERT INTO publication_title_tokens (token, publication_id) VALUES (?, ?)",
+        )?;
+        let mut authorship_stmt = tx.prepare_cached(
+            "INSERT INTO authorship (publication_id, author_name, person_id, year) VALUES (?, ?, ?, ?)",
+        )?;
+        let mut person_lookup_stmt =
+            tx.prepare_cached("SELECT id FROM persons WHERE name = ? LIMIT 1")?;
+
+        for publication in records.iter() {
+            insert_publication_row(
+                &tx,
+                &mut stmt,
+                &mut token_stmt,
+                &mut title_token_stmt,
+                &mut authorship_stmt,
+                &mut person_lookup_stmt,
+                publication,
+            )?;
+        }
     }
 
-    drop(stmt);
     tx.commit()?;
 
     Ok(())
 }
 
+/// Deserializes one already-wrapped XML chunk (e.g. `<dblp>...</dblp>`) and inserts
+/// its records into the database. Shared by the in-memory and streaming ingestion paths.
+///
+/// Authors are run through [crate::dataset::identity::canonicalize_authors]
+/// right after parsing, before the chunk is squashed into [DblpRecord]/
+/// [PersonRecord] rows.
+fn insert_xml_chunk(conn: &mut DbConnection, chunk: &str) -> rusqlite::Result<()> {
+    let mut dblp: crate::dataset::xml_items::RawDblp = quick_xml::de::from_str(chunk).unwrap();
+    crate::dataset::identity::canonicalize_authors(&mut dblp);
+
+    let (publications, persons): (Vec<DblpRecord>, Vec<PersonRecord>) = dblp.into();
+
+    dump_into_database(conn, &publications, &persons)
+}
+
 /// Deserialize the XML in chunks and insert into the database.
 /// The input XML should already be filtered of references.
 ///
@@ -208,12 +425,55 @@ pub fn chunked_deserialize_insert(conn: &mut DbConnection, xml_str: &str) -> rus
         std::io::stdout().flush().unwrap();
         chunk_number += 1;
 
-        let dblp: crate::dataset::xml_items::RawDblp = quick_xml::de::from_str(&chunk).unwrap();
+        insert_xml_chunk(conn, &chunk)?;
+    }
+    println!();
+    println!("creating index...");
+    create_all_indexes(conn)?;
 
-        let (publications, persons): (Vec<DblpRecord>, Vec<PersonRecord>) = dblp.into();
+    Ok(())
+}
+
+/// Streams `reader` (an XML document, decompressed if necessary) into the database in
+/// batches of `batch_size` level-1 elements, applying DTD entity resolution per
+/// element rather than over the whole document. Keeps memory bounded regardless
+/// of how large the input is, unlike [chunked_deserialize_insert].
+pub fn stream_deserialize_insert<R: std::io::BufRead>(
+    conn: &mut DbConnection,
+    reader: R,
+    entities: &crate::dataset::EntityMap,
+    batch_size: usize,
+) -> rusqlite::Result<()> {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut result: rusqlite::Result<()> = Ok(());
+    let mut elements_seen = 0usize;
+
+    {
+        let conn = &mut *conn;
+        crate::dataset::stream_elements(reader, entities, |element| {
+            if result.is_err() {
+                return;
+            }
 
-        dump_into_database(conn, &publications, &persons)?;
+            batch.push(element);
+            elements_seen += 1;
+            print!("\rProcessed {} elements", elements_seen);
+            std::io::stdout().flush().unwrap();
+
+            if batch.len() >= batch_size {
+                result = insert_xml_chunk(conn, &format!("<dblp>{}</dblp>", batch.join("")));
+                batch.clear();
+            }
+        })
+        .map_err(|e| rusqlite::Error::ModuleError(e.to_string()))?;
     }
+
+    result?;
+
+    if !batch.is_empty() {
+        insert_xml_chunk(conn, &format!("<dblp>{}</dblp>", batch.join("")))?;
+    }
+
     println!();
     println!("creating index...");
     create_all_indexes(conn)?;
@@ -221,12 +481,280 @@ pub fn chunked_deserialize_insert(conn: &mut DbConnection, xml_str: &str) -> rus
     Ok(())
 }
 
+/// Reads the persisted dataset-level `mdate` watermark: the newest `mdate`
+/// across every publication [update_from_xml_stream] has ingested so far.
+pub fn dataset_watermark(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    conn.query_row("SELECT mdate FROM dataset_watermark LIMIT 1", (), |r| {
+        r.get(0)
+    })
+    .optional()
+}
+
+/// Replaces the persisted watermark with `mdate`.
+fn set_dataset_watermark(conn: &Connection, mdate: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM dataset_watermark", ())?;
+    conn.execute("INSERT INTO dataset_watermark (mdate) VALUES (?)", (mdate,))?;
+    Ok(())
+}
+
+/// Upserts one XML chunk's records against in-memory snapshots of what's
+/// already stored (`existing_publications`/`existing_persons`, keyed the same
+/// way the `publications.key`/`persons.profile` columns are), tracking every
+/// key/profile seen so [update_from_xml_stream] can prune rows that have
+/// disappeared once every chunk has been processed.
+///
+/// A publication already on disk is only replaced if the incoming record's
+/// `mdate` is strictly newer (string comparison is enough - `mdate` is always
+/// rendered as an ISO `YYYY-MM-DD` date, so it sorts the same lexically as
+/// chronologically). Persons carry no `mdate` at all (DBLP's `www` records
+/// don't have one), so an existing person is only replaced if its `name` or
+/// `aliases` actually changed - otherwise its row (and `id`, which
+/// `authorship.person_id` keys off of) is left untouched.
+fn upsert_xml_chunk(
+    conn: &mut DbConnection,
+    chunk: &str,
+    existing_publications: &HashMap<String, (i64, Option<String>)>,
+    existing_persons: &HashMap<String, (i64, String, String)>,
+    seen_publication_keys: &mut HashSet<String>,
+    seen_person_profiles: &mut HashSet<String>,
+    newest_mdate: &mut Option<String>,
+) -> rusqlite::Result<()> {
+    let mut dblp: crate::dataset::xml_items::RawDblp = quick_xml::de::from_str(chunk).unwrap();
+    crate::dataset::identity::canonicalize_authors(&mut dblp);
+    let (publications, persons): (Vec<DblpRecord>, Vec<PersonRecord>) = dblp.into();
+
+    let tx = conn.transaction()?;
+
+    // persons are upserted first so `insert_publication_row`'s
+    // `person_lookup_stmt` can resolve authors introduced in this same chunk.
+    {
+        let mut stmt =
+            tx.prepare_cached("INSERT INTO persons (name, profile, aliases) VALUES (?, ?, ?)")?;
+        let mut token_stmt =
+            tx.prepare_cached("INSERT INTO person_name_tokens (token, person_id) VALUES (?, ?)")?;
+        let mut delete_stmt = tx.prepare_cached("DELETE FROM persons WHERE id = ?")?;
+        let mut delete_tokens_stmt =
+            tx.prepare_cached("DELETE FROM person_name_tokens WHERE person_id = ?")?;
+
+        for person in persons.iter() {
+            seen_person_profiles.insert(person.profile.clone());
+
+            match existing_persons.get(&person.profile) {
+                Some((_, name, aliases)) if *name == person.name && *aliases == person.aliases => {
+                    continue;
+                }
+                Some((id, _, _)) => {
+                    delete_stmt.execute((id,))?;
+                    delete_tokens_stmt.execute((id,))?;
+                }
+                None => (),
+            }
+
+            insert_person_row(&tx, &mut stmt, &mut token_stmt, person)?;
+        }
+    }
+
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO publications
+        (record, key, mdate, publtype, year, authors, citations, publisher, school, title)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        let mut token_stmt = tx.prepare_cached(
+            "INSERT INTO publication_key_tokens (token, publication_id) VALUES (?, ?)",
+        )?;
+        let mut title_token_stmt = tx.prepare_cached(
+            "INSERT INTO publication_title_tokens (token, publication_id) VALUES (?, ?)",
+        )?;
+        let mut authorship_stmt = tx.prepare_cached(
+            "INSERT INTO authorship (publication_id, author_name, person_id, year) VALUES (?, ?, ?, ?)",
+        )?;
+        let mut person_lookup_stmt =
+            tx.prepare_cached("SELECT id FROM persons WHERE name = ? LIMIT 1")?;
+        let mut delete_stmt = tx.prepare_cached("DELETE FROM publications WHERE id = ?")?;
+        let mut delete_tokens_stmt =
+            tx.prepare_cached("DELETE FROM publication_key_tokens WHERE publication_id = ?")?;
+        let mut delete_title_tokens_stmt =
+            tx.prepare_cached("DELETE FROM publication_title_tokens WHERE publication_id = ?")?;
+        let mut delete_authorship_stmt =
+            tx.prepare_cached("DELETE FROM authorship WHERE publication_id = ?")?;
+
+        for publication in publications.iter() {
+            seen_publication_keys.insert(publication.key.clone());
+
+            if publication.mdate.as_deref() > newest_mdate.as_deref() {
+                *newest_mdate = publication.mdate.clone();
+            }
+
+            match existing_publications.get(&publication.key) {
+                Some((_, existing_mdate))
+                    if publication.mdate.as_deref() <= existing_mdate.as_deref() =>
+                {
+                    continue;
+                }
+                Some((id, _)) => {
+                    delete_stmt.execute((id,))?;
+                    delete_tokens_stmt.execute((id,))?;
+                    delete_title_tokens_stmt.execute((id,))?;
+                    delete_authorship_stmt.execute((id,))?;
+                }
+                None => (),
+            }
+
+            insert_publication_row(
+                &tx,
+                &mut stmt,
+                &mut token_stmt,
+                &mut title_token_stmt,
+                &mut authorship_stmt,
+                &mut person_lookup_stmt,
+                publication,
+            )?;
+        }
+    }
+
+    tx.commit()
+}
+
+/// Incrementally refreshes the database from a *full* DBLP dump at `reader`
+/// (not a delta/diff file - every record dblp currently considers live must
+/// be present, since anything missing is treated as deleted), instead of
+/// [stream_deserialize_insert]'s `clear_tables` + full reparse.
+///
+/// A publication is only rewritten if its `mdate` is newer than what's
+/// already stored; unchanged publications are still read but touch the
+/// database only if their key is new. Persons carry no `mdate`, so a person
+/// is only written if their profile is new or their `name`/`aliases` changed.
+/// Once every chunk is processed, any stored publication/person whose key
+/// wasn't seen in `reader` is deleted, and the dataset-level watermark
+/// ([dataset_watermark]) is bumped to the newest `mdate` seen.
+pub fn update_from_xml_stream<R: std::io::BufRead>(
+    conn: &mut DbConnection,
+    reader: R,
+    entities: &crate::dataset::EntityMap,
+    batch_size: usize,
+) -> rusqlite::Result<()> {
+    let existing_publications = {
+        let mut stmt = conn.prepare("SELECT key, id, mdate FROM publications")?;
+        let rows = stmt.query_map((), |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                (r.get::<_, i64>(1)?, r.get::<_, Option<String>>(2)?),
+            ))
+        })?;
+        rows.filter_map(|r| r.ok()).collect::<HashMap<_, _>>()
+    };
+
+    let existing_persons = {
+        let mut stmt = conn.prepare("SELECT profile, id, name, aliases FROM persons")?;
+        let rows = stmt.query_map((), |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                (
+                    r.get::<_, i64>(1)?,
+                    r.get::<_, String>(2)?,
+                    r.get::<_, String>(3)?,
+                ),
+            ))
+        })?;
+        rows.filter_map(|r| r.ok()).collect::<HashMap<_, _>>()
+    };
+
+    let mut seen_publication_keys = HashSet::new();
+    let mut seen_person_profiles = HashSet::new();
+    let mut newest_mdate = dataset_watermark(conn)?;
+
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut result: rusqlite::Result<()> = Ok(());
+    let mut elements_seen = 0usize;
+
+    {
+        let conn = &mut *conn;
+        crate::dataset::stream_elements(reader, entities, |element| {
+            if result.is_err() {
+                return;
+            }
+
+            batch.push(element);
+            elements_seen += 1;
+            print!("\rProcessed {} elements", elements_seen);
+            std::io::stdout().flush().unwrap();
+
+            if batch.len() >= batch_size {
+                result = upsert_xml_chunk(
+                    conn,
+                    &format!("<dblp>{}</dblp>", batch.join("")),
+                    &existing_publications,
+                    &existing_persons,
+                    &mut seen_publication_keys,
+                    &mut seen_person_profiles,
+                    &mut newest_mdate,
+                );
+                batch.clear();
+            }
+        })
+        .map_err(|e| rusqlite::Error::ModuleError(e.to_string()))?;
+    }
+
+    result?;
+
+    if !batch.is_empty() {
+        upsert_xml_chunk(
+            conn,
+            &format!("<dblp>{}</dblp>", batch.join("")),
+            &existing_publications,
+            &existing_persons,
+            &mut seen_publication_keys,
+            &mut seen_person_profiles,
+            &mut newest_mdate,
+        )?;
+    }
+
+    println!();
+    println!("pruning records no longer present in the dataset...");
+
+    for (key, (id, _)) in existing_publications.iter() {
+        if seen_publication_keys.contains(key) {
+            continue;
+        }
+
+        conn.execute("DELETE FROM publications WHERE id = ?", (id,))?;
+        conn.execute(
+            "DELETE FROM publication_key_tokens WHERE publication_id = ?",
+            (id,),
+        )?;
+        conn.execute(
+            "DELETE FROM publication_title_tokens WHERE publication_id = ?",
+            (id,),
+        )?;
+        conn.execute("DELETE FROM authorship WHERE publication_id = ?", (id,))?;
+    }
+
+    for (profile, (id, _, _)) in existing_persons.iter() {
+        if seen_person_profiles.contains(profile) {
+            continue;
+        }
+
+        conn.execute("DELETE FROM persons WHERE id = ?", (id,))?;
+        conn.execute("DELETE FROM person_name_tokens WHERE person_id = ?", (id,))?;
+    }
+
+    if let Some(mdate) = &newest_mdate {
+        set_dataset_watermark(conn, mdate)?;
+    }
+
+    println!("creating index...");
+    create_all_indexes(conn)?;
+
+    Ok(())
+}
+
 /// Raw query into the publications table, given a set of constraints.
 pub fn raw_publications_query(
     conn: &Connection,
     constraints: String,
 ) -> rusqlite::Result<Vec<DblpRecord>> {
-    let mut stmt = conn.prepare(&format!("SELECT * FROM publications {};", constraints))?;
+    let mut stmt = conn.prepare_cached(&format!("SELECT * FROM publications {};", constraints))?;
 
     let rows = stmt.query_map((), |r| Ok(DblpRecord::from(r)))?;
 
@@ -238,14 +766,18 @@ pub fn raw_persons_query(
     conn: &Connection,
     constraints: String,
 ) -> rusqlite::Result<Vec<PersonRecord>> {
-    let mut stmt = conn.prepare(&format!("SELECT * FROM persons {};", constraints))?;
+    let mut stmt = conn.prepare_cached(&format!("SELECT * FROM persons {};", constraints))?;
 
     let rows = stmt.query_map((), |r| Ok(PersonRecord::from(r)))?;
 
     Ok(rows.filter_map(|r| r.ok()).collect())
 }
 
-/// Search for all records from a specific author
+/// Search for all records from a specific author.
+///
+/// `limit` is always bound as a parameter (`-1` meaning unlimited) rather than
+/// appended to the SQL text, so there are only ever two cacheable statement
+/// shapes (with/without `max_year`) regardless of how callers vary `limit`.
 pub fn query_author_publications(
     conn: &Connection,
     author: String,
@@ -253,34 +785,37 @@ pub fn query_author_publications(
     limit: Option<u32>,
 ) -> rusqlite::Result<Vec<DblpRecord>> {
     let q_author = format!("%{}{}{}%", SEPARATOR, author, SEPARATOR);
+    let q_limit = limit.map(|l| l as i64).unwrap_or(-1);
+
+    let rows = match max_year {
+        Some(year) => {
+            let mut stmt = conn.prepare_cached(
+                "SELECT * FROM publications WHERE authors LIKE ? AND year <= ? LIMIT ?",
+            )?;
+            stmt.query_map((q_author, year, q_limit), |r| Ok(DblpRecord::from(r)))?
+                .filter_map(|r| r.ok())
+                .collect()
+        }
+        None => {
+            let mut stmt =
+                conn.prepare_cached("SELECT * FROM publications WHERE authors LIKE ? LIMIT ?")?;
+            stmt.query_map((q_author, q_limit), |r| Ok(DblpRecord::from(r)))?
+                .filter_map(|r| r.ok())
+                .collect()
+        }
+    };
 
-    let mut box_q_params: Vec<Box<dyn ToSql>> = vec![Box::new(q_author)];
-
-    let mut q_string = format!("SELECT * FROM publications WHERE authors LIKE ? ");
-
-    if let Some(year) = max_year {
-        q_string.push_str("AND year <= ? ");
-        box_q_params.push(Box::new(year))
-    }
-
-    if let Some(l) = limit {
-        q_string.push_str("LIMIT ?");
-        box_q_params.push(Box::new(l))
-    }
-
-    // convert to Vec<&dyn ToSql>
-    let q_params: Vec<&dyn ToSql> = box_q_params.iter().map(|b| b.borrow()).collect::<Vec<_>>();
-
-    let mut stmt = conn.prepare(&q_string)?;
-
-    let rows = stmt.query_map(q_params.as_slice(), |r| Ok(DblpRecord::from(r)))?;
-
-    Ok(rows.filter_map(|r| r.ok()).collect())
+    Ok(rows)
 }
 
 /// Query the database for a specific author.
 ///
 /// If there is no match from author name, a search through aliases is performed.
+///
+/// `limit` is always bound as a parameter (`-1` meaning unlimited) instead of
+/// appended to the SQL text, so both the name-match and alias-match queries
+/// below keep a single cacheable shape regardless of whether callers pass a
+/// `limit`.
 pub fn query_author(
     conn: &DbConnection,
     author: String,
@@ -291,48 +826,239 @@ pub fn query_author(
         return query_author_exact(conn, &author);
     }
 
+    let q_limit = limit.map(|l| l as i64).unwrap_or(-1);
+
     // some author names have a serial number at the end, like this:
     // - "John Doe 0001"
     // so we query for that as well
-
     let mod_author = capitalize_wildcard(&author);
     let mod_author_serial = format!("{} ____", mod_author);
-    let mut box_q_params: Vec<Box<dyn ToSql>> =
-        vec![Box::new(&mod_author), Box::new(mod_author_serial)];
 
-    let mut q_string = format!("SELECT * FROM persons WHERE name LIKE ? OR name LIKE ?");
+    let mut stmt =
+        conn.prepare_cached("SELECT * FROM persons WHERE name LIKE ? OR name LIKE ? LIMIT ?")?;
+    let rows = stmt.query_map((mod_author, mod_author_serial, q_limit), |r| {
+        Ok(PersonRecord::from(r))
+    })?;
+
+    // if matches found, return
+    let initial_results = rows.filter_map(|r| r.ok()).collect::<Vec<_>>();
+    match initial_results.len() {
+        0 => (),
+        _ => return Ok(initial_results),
+    }
+    drop(stmt);
+
+    // search thru aliases if no exact match found
+    let mut stmt = conn.prepare_cached("SELECT * FROM persons WHERE aliases LIKE ? LIMIT ?")?;
+    let rows = stmt.query_map((format!("%{}%", author), q_limit), |r| {
+        Ok(PersonRecord::from(r))
+    })?;
+
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Search for persons whose name or aliases match a regular expression.
+///
+/// Requires [register_regexp_function] to already be registered on `conn`.
+pub fn query_author_regex(
+    conn: &Connection,
+    pattern: String,
+    limit: Option<u32>,
+) -> rusqlite::Result<Vec<PersonRecord>> {
+    let mut box_q_params: Vec<Box<dyn ToSql>> = vec![Box::new(pattern.clone()), Box::new(pattern)];
+
+    let mut q_string = "SELECT * FROM persons WHERE name REGEXP ? OR aliases REGEXP ? ".to_string();
 
     if let Some(l) = limit {
         q_string.push_str("LIMIT ?");
-        box_q_params.push(Box::new(l))
+        box_q_params.push(Box::new(l));
     }
 
-    // convert to Vec<&dyn ToSql>
     let q_params: Vec<&dyn ToSql> = box_q_params.iter().map(|b| b.borrow()).collect::<Vec<_>>();
+
     let mut stmt = conn.prepare(&q_string)?;
     let rows = stmt.query_map(q_params.as_slice(), |r| Ok(PersonRecord::from(r)))?;
 
-    // if matches found, return
-    let initial_results = rows.filter_map(|r| r.ok()).collect::<Vec<_>>();
-    match initial_results.len() {
-        0 => (),
-        _ => return Ok(initial_results),
-    }
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
 
-    // search thru aliases if no exact match found
-    let mut q_string = format!("SELECT * FROM persons WHERE aliases LIKE ? ");
-    let mut box_q_params: Vec<Box<dyn ToSql>> = vec![Box::new(format!("%{}%", author))];
+/// Search for publications whose (`::`-separated) authors field matches a regular
+/// expression. The pattern should include the `::` separators itself to anchor
+/// on a full author name, e.g. `::John Doe( [0-9]{4})?::`.
+///
+/// Requires [register_regexp_function] to already be registered on `conn`.
+pub fn query_publications_regex(
+    conn: &Connection,
+    pattern: String,
+    limit: Option<u32>,
+) -> rusqlite::Result<Vec<DblpRecord>> {
+    let mut box_q_params: Vec<Box<dyn ToSql>> = vec![Box::new(pattern)];
+
+    let mut q_string = "SELECT * FROM publications WHERE authors REGEXP ? ".to_string();
 
     if let Some(l) = limit {
         q_string.push_str("LIMIT ?");
-        box_q_params.push(Box::new(l))
+        box_q_params.push(Box::new(l));
     }
 
-    // convert to Vec<&dyn ToSql>
     let q_params: Vec<&dyn ToSql> = box_q_params.iter().map(|b| b.borrow()).collect::<Vec<_>>();
+
     let mut stmt = conn.prepare(&q_string)?;
+    let rows = stmt.query_map(q_params.as_slice(), |r| Ok(DblpRecord::from(r)))?;
+
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Typo-tolerant, ranked author search.
+///
+/// Candidates are narrowed with a `LIKE` prefix match against
+/// `person_name_tokens` (kept up to date row-by-row in
+/// [dump_into_database]) before being scored in memory with
+/// [crate::fuzzy::rank_candidate], so only names sharing a token prefix with
+/// `name` are ever ranked.
+pub fn query_author_fuzzy(
+    conn: &Connection,
+    name: String,
+    limit: Option<u32>,
+) -> rusqlite::Result<Vec<PersonRecord>> {
+    let query_words = crate::fuzzy::tokenize(&name);
+    if query_words.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let prefix_clauses = query_words
+        .iter()
+        .map(|_| "person_name_tokens.token LIKE ? || '%'")
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT DISTINCT persons.* FROM persons
+        JOIN person_name_tokens ON person_name_tokens.person_id = persons.id
+        WHERE {}",
+        prefix_clauses
+    ))?;
+
+    let q_params: Vec<&dyn ToSql> = query_words.iter().map(|w| w as &dyn ToSql).collect();
     let rows = stmt.query_map(q_params.as_slice(), |r| Ok(PersonRecord::from(r)))?;
 
+    let mut ranked = rows
+        .filter_map(|r| r.ok())
+        .filter_map(|person| {
+            crate::fuzzy::rank_candidate(&query_words, &person.name).map(|rank| (rank, person))
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by_key(|(rank, _)| *rank);
+
+    let results = ranked.into_iter().map(|(_, person)| person);
+    Ok(match limit {
+        Some(l) => results.take(l as usize).collect(),
+        None => results.collect(),
+    })
+}
+
+/// Typo-tolerant, ranked publication search.
+///
+/// Ranks against the publication `title`, falling back to `key` for records
+/// with no title at all. Narrowed via `publication_title_tokens` (kept up to
+/// date in [dump_into_database]/[upsert_xml_chunk], tokenized from the same
+/// title-or-key fallback), then ranked in-memory the same way as
+/// [query_author_fuzzy].
+pub fn query_publication_fuzzy(
+    conn: &Connection,
+    title: String,
+    limit: Option<u32>,
+) -> rusqlite::Result<Vec<DblpRecord>> {
+    let query_words = crate::fuzzy::tokenize(&title);
+    if query_words.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let prefix_clauses = query_words
+        .iter()
+        .map(|_| "publication_title_tokens.token LIKE ? || '%'")
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT DISTINCT publications.* FROM publications
+        JOIN publication_title_tokens ON publication_title_tokens.publication_id = publications.id
+        WHERE {}",
+        prefix_clauses
+    ))?;
+
+    let q_params: Vec<&dyn ToSql> = query_words.iter().map(|w| w as &dyn ToSql).collect();
+    let rows = stmt.query_map(q_params.as_slice(), |r| Ok(DblpRecord::from(r)))?;
+
+    let mut ranked = rows
+        .filter_map(|r| r.ok())
+        .filter_map(|publication| {
+            let title_or_key = publication
+                .title
+                .as_deref()
+                .unwrap_or(&publication.key)
+                .to_string();
+            crate::fuzzy::rank_candidate(&query_words, &title_or_key)
+                .map(|rank| (rank, publication))
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by_key(|(rank, _)| *rank);
+
+    let results = ranked.into_iter().map(|(_, publication)| publication);
+    Ok(match limit {
+        Some(l) => results.take(l as usize).collect(),
+        None => results.collect(),
+    })
+}
+
+/// Runs a lowered [crate::query_builder::QueryBuilder] statement, mapping
+/// each returned row with `row_fn`. Shared by [query_persons_builder] and
+/// [query_publications_builder].
+fn query_builder_rows<T>(
+    conn: &Connection,
+    builder: &crate::query_builder::QueryBuilder,
+    row_fn: impl Fn(&rusqlite::Row) -> T,
+) -> rusqlite::Result<Vec<T>> {
+    let (sql, params) = builder.to_sql();
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let q_params: Vec<&dyn ToSql> = params.iter().map(|p| p as &dyn ToSql).collect();
+
+    let rows = stmt.query_map(q_params.as_slice(), |r| Ok(row_fn(r)))?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Runs a [crate::query_builder::QueryBuilder] built with
+/// [crate::query_builder::QueryBuilder::persons] against the `persons` table.
+pub fn query_persons_builder(
+    conn: &Connection,
+    builder: &crate::query_builder::QueryBuilder,
+) -> rusqlite::Result<Vec<PersonRecord>> {
+    query_builder_rows(conn, builder, |r| PersonRecord::from(r))
+}
+
+/// Runs a [crate::query_builder::QueryBuilder] built with
+/// [crate::query_builder::QueryBuilder::publications] against the
+/// `publications` table.
+pub fn query_publications_builder(
+    conn: &Connection,
+    builder: &crate::query_builder::QueryBuilder,
+) -> rusqlite::Result<Vec<DblpRecord>> {
+    query_builder_rows(conn, builder, |r| DblpRecord::from(r))
+}
+
+/// Runs a lowered [crate::query_builder::CoauthorQuery], returning each
+/// matching publication's `(year, authors)` pair.
+pub fn query_coauthors(
+    conn: &Connection,
+    query: &crate::query_builder::CoauthorQuery,
+) -> rusqlite::Result<Vec<(Option<u32>, Option<String>)>> {
+    let (sql, params) = query.to_sql();
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let q_params: Vec<&dyn ToSql> = params.iter().map(|p| p as &dyn ToSql).collect();
+
+    let rows = stmt.query_map(q_params.as_slice(), |r| Ok((r.get(0)?, r.get(1)?)))?;
     Ok(rows.filter_map(|r| r.ok()).collect())
 }
 
@@ -353,9 +1079,9 @@ pub fn query_publication(
     key: String,
     limit: Option<u32>,
 ) -> rusqlite::Result<Vec<DblpRecord>> {
-    let mut box_q_params: Vec<Box<dyn ToSql>> = vec![Box::new(key)];
+    let mut box_q_params: Vec<Box<dyn ToSql>> = vec![Box::new(format!("%{}%", key))];
 
-    let mut q_string = format!("SELECT * FROM persons WHERE aliases LIKE ? ");
+    let mut q_string = format!("SELECT * FROM publications WHERE title LIKE ? ");
 
     if let Some(l) = limit {
         q_string.push_str("LIMIT ?");
@@ -372,10 +1098,89 @@ pub fn query_publication(
     Ok(rows.filter_map(|r| r.ok()).collect())
 }
 
-/// Create an in-memory subset of the database, where the publications
-/// are filtered by coauthors and year range.
+/// Registers the CSV file at `path` as a temporary virtual table named
+/// `table_name`, using rusqlite's `csvtab` module. Precomputed coauthor or
+/// citation edge lists can then be copied straight into `persons`/
+/// `publications` with `INSERT ... SELECT`, without an XML round-trip.
+pub fn load_csv_table(
+    conn: &Connection,
+    path: impl AsRef<Path>,
+    table_name: &str,
+) -> rusqlite::Result<()> {
+    rusqlite::vtab::csvtab::load_module(conn)?;
+
+    conn.execute_batch(&format!(
+        "CREATE VIRTUAL TABLE temp.{table} USING csv(filename={path}, header=yes);",
+        table = table_name,
+        path = sql_quote(&path.as_ref().to_string_lossy()),
+    ))
+}
+
+/// Streams the results of an arbitrary `SELECT` query out as CSV, header row
+/// included, so tooling outside this crate (e.g. network-analysis pipelines
+/// expecting an edge list) can consume query output directly.
+pub fn export_query_csv<W: Write>(conn: &Connection, sql: &str, writer: W) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(sql)?;
+    let column_names = stmt
+        .column_names()
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer
+        .write_record(&column_names)
+        .map_err(|e| rusqlite::Error::ModuleError(e.to_string()))?;
+
+    let mut rows = stmt.query(())?;
+    while let Some(row) = rows.next()? {
+        let record = (0..column_names.len())
+            .map(|i| {
+                row.get::<usize, rusqlite::types::Value>(i)
+                    .map(|v| csv_value_to_string(&v))
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        csv_writer
+            .write_record(&record)
+            .map_err(|e| rusqlite::Error::ModuleError(e.to_string()))?;
+    }
+
+    csv_writer
+        .flush()
+        .map_err(|e| rusqlite::Error::ModuleError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Renders a raw sqlite [rusqlite::types::Value] as a CSV field.
+fn csv_value_to_string(value: &rusqlite::types::Value) -> String {
+    match value {
+        rusqlite::types::Value::Null => String::new(),
+        rusqlite::types::Value::Integer(i) => i.to_string(),
+        rusqlite::types::Value::Real(f) => f.to_string(),
+        rusqlite::types::Value::Text(s) => s.clone(),
+        rusqlite::types::Value::Blob(b) => String::from_utf8_lossy(b).into_owned(),
+    }
+}
+
+/// Escapes a value for interpolation into a single-quoted SQL literal.
+///
+/// `execute_batch` runs every statement in a string, but (unlike `execute`) it
+/// doesn't accept bound parameters at all, so the `ATTACH`/`INSERT ... SELECT`
+/// pipeline below has to inline its literals directly.
+fn sql_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Create a subset of the database containing only the given authors and the
+/// publications attributed to them up to `end`, returning a connection pool to
+/// the subset database file (`subset.sqlite`).
 ///
-/// Returns the connection pool to the subset database.
+/// Attaches the subset file to `conn` and copies rows across with
+/// `INSERT ... SELECT` inside a single transaction, rather than streaming every
+/// row over channels into a second set of writer threads.
 #[allow(unused)]
 pub fn create_subset_database(
     conn: &DbConnection,
@@ -383,109 +1188,94 @@ pub fn create_subset_database(
     start: u32,
     end: u32,
 ) -> rusqlite::Result<Pool<SqliteConnectionManager>> {
-    let mgr = SqliteConnectionManager::file("subset.sqlite"); // temp file
-    let pool = Pool::new(mgr).unwrap();
-
-    let s_conn = pool.get().unwrap();
-
-    clear_tables(&s_conn)?;
-    create_tables(&s_conn)?;
-
-    // author task
-    let (a_tx, a_rx) = mpsc::channel::<PersonRecord>();
-    let p_h1 = pool.clone();
-    let h1 = std::thread::spawn(move || {
-        let mut conn = p_h1.get().unwrap();
-
-        let transaction = conn.transaction().expect("failed to create transaction");
-        let mut stmt = transaction
-            .prepare(
-                "INSERT INTO persons
-            (name, profile, aliases)
-            VALUES (?, ?, ?)",
-            )
-            .expect("failed to create prepare statement");
-
-        while let Ok(data) = a_rx.recv() {
-            stmt.execute((
-                data.name.to_owned(),
-                data.profile.to_owned(),
-                data.aliases.to_owned(),
-            ))
-            .expect("failed to insert data");
-        }
-
-        drop(stmt);
-        transaction.finish().expect("failed to ocmmit transaction");
-    });
-
-    // publication task
-    let (p_tx, p_rx) = mpsc::channel::<DblpRecord>();
-    let p_h2 = pool.clone();
-    let h2 = std::thread::spawn(move || {
-        let mut conn = p_h2.get().unwrap();
-
-        let transaction = conn.transaction().expect("failed to create transaction");
-
-        let mut stmt = transaction
-            .prepare(
-                "INSERT INTO publications
-            (record, key, mdate, publtype, year, authors, citations, publisher, school)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            )
-            .expect("failed to create prepare statement");
-
-        while let Ok(data) = p_rx.recv() {
-            stmt.execute((
-                data.record.to_string(),
-                data.key.to_owned(),
-                data.mdate.to_owned(),
-                data.publtype.to_owned(),
-                data.year.to_owned(),
-                data.authors.to_owned(),
-                data.citations.to_owned(),
-                data.publisher.to_owned(),
-                data.school.to_owned(),
-            ))
-            .expect("failed to insert data");
-        }
+    const SUBSET_PATH: &str = "subset.sqlite";
 
-        drop(stmt);
-        transaction.finish().expect("failed to commit transaction");
-    });
+    let mgr = connection_manager(SUBSET_PATH, ConnectionOptions::default());
+    let pool = Pool::new(mgr).unwrap();
 
     let mut insert_set = HashSet::<u32>::new();
+    let mut publication_ids = Vec::new();
 
     for author in authors {
-        let x = query_author_publications(&conn, author.name.clone(), Some(end), None)?;
-
-        let insert = x
-            .into_iter()
-            .filter_map(|record| match insert_set.contains(&record.id) {
-                true => None,
-                false => {
-                    insert_set.insert(record.id);
-                    Some(record)
-                }
-            });
+        let publications = query_author_publications(conn, author.name.clone(), Some(end), None)?;
 
-        for record in insert {
-            p_tx.send(record).expect("failed to send data");
+        for record in publications {
+            if insert_set.insert(record.id) {
+                publication_ids.push(record.id);
+            }
         }
     }
 
-    for a in authors {
-        a_tx.send(a.clone()).expect("failed to send data");
-    }
+    let profiles = authors
+        .iter()
+        .map(|a| sql_quote(&a.profile))
+        .collect::<Vec<_>>()
+        .join(",");
 
-    drop(a_tx);
-    drop(p_tx);
-    h1.join();
-    h2.join();
+    let publication_ids = publication_ids
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    conn.execute_batch(&format!(
+        "ATTACH DATABASE {subset_path} AS subset_db;
+        CREATE TABLE IF NOT EXISTS subset_db.persons(
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            profile TEXT NOT NULL,
+            aliases TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS subset_db.publications(
+            id INTEGER PRIMARY KEY,
+            record TEXT NOT NULL,
+            key TEXT NOT NULL,
+            mdate TEXT,
+            publtype TEXT,
+            year INTEGER,
+            authors TEXT,
+            citations TEXT,
+            publisher TEXT,
+            school TEXT,
+            title TEXT
+        );
+        BEGIN;
+        DELETE FROM subset_db.persons;
+        DELETE FROM subset_db.publications;
+        INSERT INTO subset_db.persons SELECT * FROM persons WHERE profile IN ({profiles});
+        INSERT INTO subset_db.publications SELECT * FROM publications WHERE id IN ({ids});
+        COMMIT;
+        DETACH DATABASE subset_db;",
+        subset_path = sql_quote(SUBSET_PATH),
+        profiles = if profiles.is_empty() {
+            "NULL".to_string()
+        } else {
+            profiles
+        },
+        ids = if publication_ids.is_empty() {
+            "NULL".to_string()
+        } else {
+            publication_ids
+        },
+    ))?;
 
     Ok(pool)
 }
 
+/// Snapshots `src_conn`'s database into the sqlite file at `dest_path`, using
+/// SQLite's online backup API. Lets callers copy a full or subset database
+/// without attaching another file or standing up channel/thread plumbing.
+pub fn backup_database(
+    src_conn: &Connection,
+    dest_path: impl AsRef<Path>,
+    mut progress: impl FnMut(rusqlite::backup::Progress),
+) -> rusqlite::Result<()> {
+    let mut dest = Connection::open(dest_path)?;
+    let backup = rusqlite::backup::Backup::new(src_conn, &mut dest)?;
+
+    backup.run_to_completion(5, Duration::from_millis(250), Some(&mut progress))
+}
+
 /// Capitalize the first letter of a name and insert the '%' wildcard in spaces.
 fn capitalize_wildcard(input: &str) -> String {
     input
@@ -506,7 +1296,7 @@ mod tests {
 
     use crate::{
         dataset::{strip_references, xml_items::RawDblp},
-        get_init_conn_pool, DB_PATH,
+        test_support::temp_sqlite_path,
     };
 
     use super::*;
@@ -547,35 +1337,40 @@ mod tests {
         assert_eq!(capitalize_wildcard(input), expected);
     }
 
-    /// Test if rusqlite can copy data from one db to another.
-    /// Does not work
+    /// `Connection::execute` only runs the first statement of a multi-statement
+    /// string, so `ATTACH ...; INSERT ...` needs `execute_batch` instead.
     #[test]
     fn test_database_copy() -> rusqlite::Result<()> {
-        let mgr = SqliteConnectionManager::file("subset.sqlite");
-        let pool = Pool::new(mgr).unwrap();
-
-        // clear_tables(conn)
-        create_tables(&pool.get().unwrap())?;
-
-        // init og database
-        DB_PATH.get_or_init(|| "../dblp.sqlite".to_string());
+        let (_src_dir, src_path) = temp_sqlite_path();
+        let src_pool = Pool::new(connection_manager(&src_path, ConnectionOptions::default()))
+            .expect("failed to create pool");
+        let src_conn = src_pool.get().unwrap();
+
+        create_tables(&src_conn)?;
+        src_conn.execute(
+            "INSERT INTO persons (name, profile, aliases) VALUES (?, ?, ?)",
+            ("Jane Doe", "homepages/j/JaneDoe", ""),
+        )?;
 
-        let conn = get_init_conn_pool();
+        let (_dest_dir, dest_path) = temp_sqlite_path();
+        let dest_pool = Pool::new(connection_manager(&dest_path, ConnectionOptions::default()))
+            .expect("failed to create pool");
+        create_tables(&dest_pool.get().unwrap())?;
 
-        let res = conn.query_row("SELECT * FROM persons LIMIT 10", (), |r| {
-            r.get::<usize, String>(1)
-        })?;
-        // assert_eq!(res, 10);
-        println!("{}", res);
+        src_conn.execute_batch(&format!(
+            "ATTACH DATABASE {dest} AS subset_db;
+            INSERT INTO subset_db.persons SELECT * FROM persons;
+            DETACH DATABASE subset_db;",
+            dest = sql_quote(dest_path.to_str().unwrap()),
+        ))?;
 
-        let res = conn.execute(
-            "ATTACH DATABASE 'subset.sqlite' AS subset_db;
-        INSERT INTO subset_db.persons SELECT * FROM persons",
-            (),
-        )?;
-        // let res = conn.execute("INSERT INTO subset_db.persons SELECT * FROM persons", ())?;
+        let copied: u32 =
+            dest_pool
+                .get()
+                .unwrap()
+                .query_row("SELECT COUNT(*) FROM persons", (), |r| r.get(0))?;
 
-        // create_tables(conn)
+        assert_eq!(copied, 1);
 
         Ok(())
     }
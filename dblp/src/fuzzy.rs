@@ -0,0 +1,172 @@
+//! Typo-tolerant ranking for fuzzy author/publication search.
+//!
+//! This module only holds the text-matching algorithm - tokenizing, bounding
+//! a Levenshtein distance by a typo budget, and turning the matches into a
+//! sortable rank. The SQL side (narrowing candidates via the token-prefix
+//! index before ranking them here) lives in [crate::db], alongside the rest
+//! of the crate's queries.
+
+/// The number of single-character edits a query word of this length may
+/// still differ from a candidate word by and be considered a match.
+///
+/// Scaled by length so short words (where a couple of edits would just match
+/// everything) stay strict, while longer names can absorb the odd typo or
+/// transliteration difference.
+pub fn typo_budget(word_len: usize) -> usize {
+    match word_len {
+        0..=4 => 0,
+        5..=8 => 1,
+        _ => 2,
+    }
+}
+
+/// Lowercases `s` and splits it into alphanumeric words, discarding
+/// punctuation and whitespace.
+pub fn tokenize(s: &str) -> Vec<String> {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// Banded Levenshtein distance between `a` and `b`, bounded by `max_dist`.
+///
+/// Only cells within `max_dist` of the diagonal are ever computed, so this
+/// stays cheap even when scanning many candidate words - `max_dist` never
+/// exceeds 2 here (see [typo_budget]). Returns `None` once the words are
+/// provably further apart than `max_dist`.
+pub fn bounded_levenshtein(a: &str, b: &str, max_dist: usize) -> Option<usize> {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    if a.len().abs_diff(b.len()) > max_dist {
+        return None;
+    }
+
+    const UNREACHABLE: usize = usize::MAX / 2;
+
+    let mut prev_row = vec![UNREACHABLE; b.len() + 1];
+    let mut curr_row = vec![UNREACHABLE; b.len() + 1];
+
+    for (j, cell) in prev_row.iter_mut().enumerate().take(max_dist + 1) {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        curr_row.iter_mut().for_each(|cell| *cell = UNREACHABLE);
+
+        if i <= max_dist {
+            curr_row[0] = i;
+        }
+
+        let lo = i.saturating_sub(max_dist).max(1);
+        let hi = (i + max_dist).min(b.len());
+
+        for j in lo..=hi {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            curr_row[j] = (prev_row[j] + 1)
+                .min(curr_row[j - 1] + 1)
+                .min(prev_row[j - 1] + cost);
+        }
+
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+
+    let distance = prev_row[b.len()];
+    (distance <= max_dist).then_some(distance)
+}
+
+/// A single query word matched against the closest word in a candidate
+/// string.
+struct WordMatch {
+    typos: usize,
+    is_prefix: bool,
+    position: usize,
+}
+
+/// Ranking key for one candidate against a (tokenized) query, ordered the way
+/// a search engine would rank results - smaller is always better:
+///
+/// 1. fewer typos across all matched words
+/// 2. more of the query's words matched (fewer left unmatched)
+/// 3. matched words are exact prefixes of the candidate's words
+/// 4. matched words sit closer together in the candidate
+/// 5. shorter candidate field, as a final tiebreak
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RankKey {
+    total_typos: usize,
+    words_unmatched: usize,
+    not_all_prefix: bool,
+    proximity: usize,
+    field_len: usize,
+}
+
+/// Scores `candidate` against `query_words` (see [tokenize]), returning
+/// `None` if not a single query word matched within its typo budget.
+pub fn rank_candidate(query_words: &[String], candidate: &str) -> Option<RankKey> {
+    let candidate_words = tokenize(candidate);
+    if candidate_words.is_empty() {
+        return None;
+    }
+
+    let matches = query_words
+        .iter()
+        .filter_map(|query_word| {
+            let budget = typo_budget(query_word.len());
+
+            candidate_words
+                .iter()
+                .enumerate()
+                .filter_map(|(position, candidate_word)| {
+                    bounded_levenshtein(query_word, candidate_word, budget).map(|typos| WordMatch {
+                        typos,
+                        is_prefix: candidate_word.starts_with(query_word.as_str()),
+                        position,
+                    })
+                })
+                .min_by_key(|m| (m.typos, !m.is_prefix))
+        })
+        .collect::<Vec<_>>();
+
+    if matches.is_empty() {
+        return None;
+    }
+
+    let total_typos = matches.iter().map(|m| m.typos).sum();
+    let words_unmatched = query_words.len() - matches.len();
+    let not_all_prefix = !matches.iter().all(|m| m.is_prefix);
+
+    let mut positions = matches.iter().map(|m| m.position).collect::<Vec<_>>();
+    positions.sort_unstable();
+    positions.dedup();
+    let proximity = match positions.len() {
+        0 | 1 => 0,
+        n => positions[n - 1]
+            .saturating_sub(positions[0])
+            .saturating_sub(n - 1),
+    };
+
+    Some(RankKey {
+        total_typos,
+        words_unmatched,
+        not_all_prefix,
+        proximity,
+        field_len: candidate.chars().count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A repeated query word matching the same candidate token twice
+    /// (`positions == [0, 0]`) used to underflow `positions[n-1] - positions[0]
+    /// - (n-1)` since both matched positions are equal.
+    #[test]
+    fn test_rank_candidate_repeated_query_word_does_not_panic() {
+        let query_words = vec!["john".to_string(), "john".to_string()];
+        assert!(rank_candidate(&query_words, "john smith").is_some());
+    }
+}
@@ -0,0 +1,277 @@
+//! Coauthorship graph export: map each publication's author list to
+//! coauthor pairs, reduce them into weighted edges, then serialize the
+//! result as a plain edge-list CSV or GraphML - either loads straight into
+//! networkx/igraph.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{self, BufWriter},
+    str::FromStr,
+};
+
+use pyo3::{exceptions::PyTypeError, pyfunction, PyResult};
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    dataset::db_items::{PersonRecord, SEPARATOR},
+    db, get_init_conn_pool,
+};
+
+/// An undirected coauthorship edge, aggregated over every shared publication
+/// within the requested year range. `a`/`b` are ordered so `(a, b)` is a
+/// stable key regardless of which author a publication lists first.
+#[derive(Debug, Clone)]
+struct CoauthorEdge {
+    a: String,
+    b: String,
+    weight: u32,
+    first_year: u32,
+    last_year: u32,
+}
+
+/// Output format for [export_coauthor_graph].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphFormat {
+    EdgeList,
+    GraphMl,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" | "edgelist" | "edge_list" => Ok(GraphFormat::EdgeList),
+            "graphml" => Ok(GraphFormat::GraphMl),
+            other => Err(format!("unknown graph export format `{}`", other)),
+        }
+    }
+}
+
+fn ordered_pair<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Builds the coauthorship graph for `persons` over `[year_start, year_end]`
+/// and writes it to `target`, as either a weighted edge-list CSV or GraphML
+/// (`format`, case-insensitive: `"csv"`/`"edgelist"` or `"graphml"`).
+///
+/// Each publication is mapped to every coauthor pair among its authors that
+/// are also in `persons` (the map step), then folded into aggregated edges -
+/// weight is the number of shared publications, alongside the first/last
+/// year they collaborated (the reduce step). Publications are processed in
+/// rayon chunks, the same parallelization `temporal_relation` uses.
+#[pyfunction]
+pub fn export_coauthor_graph(
+    persons: Vec<PersonRecord>,
+    year_start: u32,
+    year_end: u32,
+    format: String,
+    target: String,
+) -> PyResult<()> {
+    let format = format
+        .parse::<GraphFormat>()
+        .map_err(PyTypeError::new_err)?;
+
+    let names = persons
+        .iter()
+        .map(|p| p.name.clone())
+        .collect::<HashSet<_>>();
+
+    let conn = get_init_conn_pool();
+
+    // `query_author_publications` is run once per name, so a publication
+    // coauthored by two or more people in `persons` comes back once per
+    // matching author - dedupe by key before the map/reduce pass, or its
+    // coauthor pairs would be counted once per repeat instead of once per
+    // shared publication.
+    let publications = names
+        .iter()
+        .map(|name| db::query_author_publications(&conn, name.clone(), Some(year_end), None))
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| PyTypeError::new_err(e.to_string()))?
+        .into_iter()
+        .flatten()
+        .filter(|p| p.year.is_some_and(|y| y >= year_start))
+        .map(|p| (p.key.clone(), p))
+        .collect::<HashMap<_, _>>()
+        .into_values()
+        .collect::<Vec<_>>();
+
+    // map: each publication's author list to the coauthor pairs it implies
+    // (restricted to `names`); reduce: fold every chunk's pairs into one
+    // edge map, then fold the per-chunk maps together.
+    let edges = publications
+        .par_chunks(64)
+        .map(|chunk| {
+            let mut local = HashMap::<(String, String), CoauthorEdge>::new();
+
+            for publication in chunk {
+                let Some(year) = publication.year else {
+                    continue;
+                };
+                let Some(authors) = publication.authors.as_deref() else {
+                    continue;
+                };
+
+                let authors = authors
+                    .trim_matches(|c| c == ':')
+                    .split(SEPARATOR)
+                    .filter(|a| names.contains(*a))
+                    .collect::<Vec<_>>();
+
+                for i in 0..authors.len() {
+                    for j in (i + 1)..authors.len() {
+                        let (a, b) = ordered_pair(authors[i], authors[j]);
+
+                        local
+                            .entry((a.to_string(), b.to_string()))
+                            .and_modify(|edge| {
+                                edge.weight += 1;
+                                edge.first_year = edge.first_year.min(year);
+                                edge.last_year = edge.last_year.max(year);
+                            })
+                            .or_insert(CoauthorEdge {
+                                a: a.to_string(),
+                                b: b.to_string(),
+                                weight: 1,
+                                first_year: year,
+                                last_year: year,
+                            });
+                    }
+                }
+            }
+
+            local
+        })
+        .reduce(HashMap::new, |mut acc, chunk_edges| {
+            for (key, edge) in chunk_edges {
+                acc.entry(key)
+                    .and_modify(|existing| {
+                        existing.weight += edge.weight;
+                        existing.first_year = existing.first_year.min(edge.first_year);
+                        existing.last_year = existing.last_year.max(edge.last_year);
+                    })
+                    .or_insert(edge);
+            }
+
+            acc
+        })
+        .into_values()
+        .collect::<Vec<_>>();
+
+    match format {
+        GraphFormat::EdgeList => write_edge_list_csv(&edges, &target),
+        GraphFormat::GraphMl => write_graphml(&edges, &target),
+    }
+    .map_err(|e| PyTypeError::new_err(e.to_string()))
+}
+
+fn write_edge_list_csv(edges: &[CoauthorEdge], target: &str) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_path(target)?;
+    writer.write_record(["source", "target", "weight", "first_year", "last_year"])?;
+
+    for edge in edges {
+        writer.write_record([
+            edge.a.as_str(),
+            edge.b.as_str(),
+            &edge.weight.to_string(),
+            &edge.first_year.to_string(),
+            &edge.last_year.to_string(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_graphml(edges: &[CoauthorEdge], target: &str) -> io::Result<()> {
+    let file = BufWriter::new(File::create(target)?);
+    let mut writer = quick_xml::Writer::new_with_indent(file, b' ', 2);
+
+    write_event(
+        &mut writer,
+        Event::Decl(quick_xml::events::BytesDecl::new(
+            "1.0",
+            Some("UTF-8"),
+            None,
+        )),
+    )?;
+
+    let mut graphml = BytesStart::new("graphml");
+    graphml.push_attribute(("xmlns", "http://graphml.graphdrawing.org/xmlns"));
+    write_event(&mut writer, Event::Start(graphml))?;
+
+    for (id, attr_name) in [
+        ("weight", "weight"),
+        ("first_year", "first_year"),
+        ("last_year", "last_year"),
+    ] {
+        let mut key = BytesStart::new("key");
+        key.push_attribute(("id", id));
+        key.push_attribute(("for", "edge"));
+        key.push_attribute(("attr.name", attr_name));
+        key.push_attribute(("attr.type", "int"));
+        write_event(&mut writer, Event::Empty(key))?;
+    }
+
+    let mut graph = BytesStart::new("graph");
+    graph.push_attribute(("id", "coauthors"));
+    graph.push_attribute(("edgedefault", "undirected"));
+    write_event(&mut writer, Event::Start(graph))?;
+
+    let mut nodes = edges
+        .iter()
+        .flat_map(|e| [e.a.as_str(), e.b.as_str()])
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    nodes.sort_unstable();
+
+    for node in nodes {
+        let mut node_tag = BytesStart::new("node");
+        node_tag.push_attribute(("id", node));
+        write_event(&mut writer, Event::Empty(node_tag))?;
+    }
+
+    for edge in edges {
+        let mut edge_tag = BytesStart::new("edge");
+        edge_tag.push_attribute(("source", edge.a.as_str()));
+        edge_tag.push_attribute(("target", edge.b.as_str()));
+        write_event(&mut writer, Event::Start(edge_tag))?;
+
+        for (key, value) in [
+            ("weight", edge.weight.to_string()),
+            ("first_year", edge.first_year.to_string()),
+            ("last_year", edge.last_year.to_string()),
+        ] {
+            let mut data = BytesStart::new("data");
+            data.push_attribute(("key", key));
+            write_event(&mut writer, Event::Start(data))?;
+            write_event(&mut writer, Event::Text(BytesText::new(&value)))?;
+            write_event(&mut writer, Event::End(BytesEnd::new("data")))?;
+        }
+
+        write_event(&mut writer, Event::End(BytesEnd::new("edge")))?;
+    }
+
+    write_event(&mut writer, Event::End(BytesEnd::new("graph")))?;
+    write_event(&mut writer, Event::End(BytesEnd::new("graphml")))?;
+
+    Ok(())
+}
+
+fn write_event<W: io::Write>(
+    writer: &mut quick_xml::Writer<W>,
+    event: Event<'_>,
+) -> io::Result<()> {
+    writer
+        .write_event(event)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
@@ -0,0 +1,340 @@
+//! Async ingestion pipeline that overlaps XML parsing with SQLite inserts.
+//!
+//! One task streams and decodes level-1 XML elements off the gzipped DBLP
+//! dump, pushing deserialized records over a bounded `mpsc` channel. A second
+//! task drains that channel and performs batched inserts inside transactions
+//! against the `rusqlite` connection. The channel's bound provides
+//! backpressure, so the parser can't run arbitrarily far ahead of the
+//! database.
+
+use std::path::{Path, PathBuf};
+
+use async_compression::tokio::bufread::GzipDecoder;
+use async_trait::async_trait;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use thiserror::Error;
+use tokio::io::{AsyncBufRead, BufReader};
+use tokio::sync::mpsc;
+
+use crate::dataset::db_items::{DblpRecord, PersonRecord};
+use crate::dataset::xml_items::{
+    Article, Book, InCollection, InProceeding, MastersThesis, PhdThesis, Proceeding, WebPage,
+};
+use crate::dataset::{resolve_references, EntityMap};
+use crate::db;
+
+/// Errors that can occur while asynchronously decoding or inserting a record.
+#[derive(Debug, Error)]
+pub enum ParsingError {
+    #[error("xml error: {0}")]
+    Xml(#[from] quick_xml::Error),
+    #[error("deserialization error: {0}")]
+    De(#[from] quick_xml::DeError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("connection pool error: {0}")]
+    Pool(#[from] r2d2::Error),
+}
+
+/// A top-level DBLP record that knows how to decode itself off an async XML
+/// event stream, given that its own opening tag has already been consumed.
+///
+/// Implementing this for a new record kind is all that's needed for it to
+/// participate in [ingest_async].
+#[async_trait]
+pub trait QRead: Sized {
+    async fn qread<R>(
+        reader: &mut Reader<R>,
+        start: BytesStart<'static>,
+        entities: &EntityMap,
+    ) -> Result<Self, ParsingError>
+    where
+        R: AsyncBufRead + Unpin + Send;
+}
+
+/// Re-reads one level-1 element's worth of events (past its opening tag,
+/// up to and including its matching closing tag) back into owned XML text,
+/// for use with `quick_xml::de`.
+///
+/// `Event::Text` is resolved against `entities` before being written back
+/// out, same as the synchronous counterpart (`dataset::read_element`) - DBLP
+/// uses named entities like `&uuml;` constantly in author names, and
+/// `quick_xml::de` errors on anything it doesn't recognize as predefined.
+async fn read_element_async<R>(
+    reader: &mut Reader<R>,
+    start: BytesStart<'static>,
+    entities: &EntityMap,
+) -> Result<String, ParsingError>
+where
+    R: AsyncBufRead + Unpin + Send,
+{
+    use quick_xml::events::BytesText;
+
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    writer.write_event(Event::Start(start))?;
+
+    let mut depth = 1;
+    let mut buf = Vec::new();
+
+    while depth != 0 {
+        let event = reader.read_event_into_async(&mut buf).await?;
+
+        match event {
+            Event::Eof => break,
+            Event::Start(s) => {
+                depth += 1;
+                writer.write_event(Event::Start(s.into_owned()))?;
+            }
+            Event::End(e) => {
+                depth -= 1;
+                writer.write_event(Event::End(e.into_owned()))?;
+            }
+            Event::Text(t) => {
+                let raw = String::from_utf8_lossy(t.as_ref()).into_owned();
+                let resolved = resolve_references(&raw, entities);
+                writer.write_event(Event::Text(BytesText::from_escaped(resolved)))?;
+            }
+            other => writer.write_event(other.into_owned())?,
+        }
+
+        buf.clear();
+    }
+
+    Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+}
+
+macro_rules! impl_qread_via_de {
+    ($ty: ty) => {
+        #[async_trait]
+        impl QRead for $ty {
+            async fn qread<R>(
+                reader: &mut Reader<R>,
+                start: BytesStart<'static>,
+                entities: &EntityMap,
+            ) -> Result<Self, ParsingError>
+            where
+                R: AsyncBufRead + Unpin + Send,
+            {
+                let xml = read_element_async(reader, start, entities).await?;
+                Ok(quick_xml::de::from_str(&xml)?)
+            }
+        }
+    };
+}
+
+impl_qread_via_de!(Article);
+impl_qread_via_de!(InProceeding);
+impl_qread_via_de!(Proceeding);
+impl_qread_via_de!(Book);
+impl_qread_via_de!(InCollection);
+impl_qread_via_de!(PhdThesis);
+impl_qread_via_de!(MastersThesis);
+impl_qread_via_de!(WebPage);
+
+/// One record decoded off the wire, tagged with which table it belongs to.
+enum IngestItem {
+    Publication(DblpRecord),
+    Person(PersonRecord),
+}
+
+/// Streams `gz_path` and writes its records into `db_path`, in batches of
+/// `batch_size`. `batch_size` sizes both the channel's backpressure bound and
+/// how many records are grouped into a single insert transaction.
+pub async fn ingest_async(
+    gz_path: impl AsRef<Path>,
+    db_path: impl AsRef<Path>,
+    entities: EntityMap,
+    batch_size: usize,
+) -> Result<(), ParsingError> {
+    let (tx, rx) = mpsc::channel::<IngestItem>(batch_size);
+
+    let gz_path = gz_path.as_ref().to_owned();
+    let parser = tokio::spawn(parse_task(gz_path, entities, tx));
+
+    let db_path = db_path.as_ref().to_owned();
+    let inserter = tokio::task::spawn_blocking(move || insert_task(db_path, rx, batch_size));
+
+    let (parse_result, insert_result) = tokio::join!(parser, inserter);
+    parse_result.expect("parse task panicked")?;
+    insert_result.expect("insert task panicked")?;
+
+    Ok(())
+}
+
+/// Decodes level-1 elements off `gz_path` and forwards them over `tx`.
+async fn parse_task(
+    gz_path: PathBuf,
+    entities: EntityMap,
+    tx: mpsc::Sender<IngestItem>,
+) -> Result<(), ParsingError> {
+    let file = tokio::fs::File::open(&gz_path).await?;
+    let decoder = GzipDecoder::new(BufReader::new(file));
+    let mut reader = Reader::from_reader(BufReader::new(decoder));
+
+    let mut buf = Vec::new();
+
+    loop {
+        let event = reader.read_event_into_async(&mut buf).await?;
+
+        match event {
+            Event::Eof => break,
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                let start = start.into_owned();
+
+                match name.as_str() {
+                    "article" => {
+                        send_publication::<Article, _>(&mut reader, start, &entities, &tx).await?
+                    }
+                    "inproceedings" => {
+                        send_publication::<InProceeding, _>(&mut reader, start, &entities, &tx)
+                            .await?
+                    }
+                    "proceedings" => {
+                        send_publication::<Proceeding, _>(&mut reader, start, &entities, &tx)
+                            .await?
+                    }
+                    "book" => {
+                        send_publication::<Book, _>(&mut reader, start, &entities, &tx).await?
+                    }
+                    "incollection" => {
+                        send_publication::<InCollection, _>(&mut reader, start, &entities, &tx)
+                            .await?
+                    }
+                    "phdthesis" => {
+                        send_publication::<PhdThesis, _>(&mut reader, start, &entities, &tx).await?
+                    }
+                    "mastersthesis" => {
+                        send_publication::<MastersThesis, _>(&mut reader, start, &entities, &tx)
+                            .await?
+                    }
+                    "www" => {
+                        let page = WebPage::qread(&mut reader, start, &entities).await?;
+                        if let Ok(person) = PersonRecord::try_from(page) {
+                            let _ = tx.send(IngestItem::Person(person)).await;
+                        }
+                    }
+                    // the outer root: descend into it, so its level-1 children are
+                    // decoded by the arms above
+                    "dblp" => (),
+                    // unrecognized level-1 element: consumed and skipped
+                    _ => {
+                        read_element_async(&mut reader, start, &entities).await?;
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        buf.clear();
+    }
+
+    Ok(())
+}
+
+async fn send_publication<T, R>(
+    reader: &mut Reader<R>,
+    start: BytesStart<'static>,
+    entities: &EntityMap,
+    tx: &mpsc::Sender<IngestItem>,
+) -> Result<(), ParsingError>
+where
+    T: QRead,
+    DblpRecord: TryFrom<T>,
+    R: AsyncBufRead + Unpin + Send,
+{
+    let record = T::qread(reader, start, entities).await?;
+
+    if let Ok(record) = DblpRecord::try_from(record) {
+        let _ = tx.send(IngestItem::Publication(record)).await;
+    }
+
+    Ok(())
+}
+
+/// Drains `rx` and writes records into `db_path` in batched transactions.
+///
+/// Runs on a blocking thread via `spawn_blocking`, since `rusqlite` is synchronous.
+fn insert_task(
+    db_path: PathBuf,
+    mut rx: mpsc::Receiver<IngestItem>,
+    batch_size: usize,
+) -> Result<(), ParsingError> {
+    let manager = r2d2_sqlite::SqliteConnectionManager::file(db_path);
+    let pool = r2d2::Pool::new(manager)?;
+    let mut conn = pool.get()?;
+
+    db::create_tables(&conn)?;
+
+    let mut publications = Vec::with_capacity(batch_size);
+    let mut persons = Vec::with_capacity(batch_size);
+
+    while let Some(item) = rx.blocking_recv() {
+        match item {
+            IngestItem::Publication(p) => publications.push(p),
+            IngestItem::Person(p) => persons.push(p),
+        }
+
+        if publications.len() + persons.len() >= batch_size {
+            flush_batch(&mut conn, &mut publications, &mut persons)?;
+        }
+    }
+
+    flush_batch(&mut conn, &mut publications, &mut persons)
+}
+
+fn flush_batch(
+    conn: &mut r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>,
+    publications: &mut Vec<DblpRecord>,
+    persons: &mut Vec<PersonRecord>,
+) -> Result<(), ParsingError> {
+    if publications.is_empty() && persons.is_empty() {
+        return Ok(());
+    }
+
+    db::dump_into_database(conn, publications, persons)?;
+
+    publications.clear();
+    persons.clear();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// The fixture's entity declarations, standing in for `dblp.dtd` so the
+    /// test doesn't depend on it being present.
+    fn fixture_entities() -> EntityMap {
+        HashMap::from([("uuml".to_string(), "ü".to_string())])
+    }
+
+    /// DBLP uses named entities like `&uuml;` constantly in author names -
+    /// unresolved, `quick_xml::de` errors with `EscapeError(UnrecognizedEntity)`
+    /// instead of deserializing the record.
+    #[tokio::test]
+    async fn test_read_element_async_resolves_entities() {
+        let xml = br#"<article mdate="2023-01-01" key="journals/test/Mueller23" publtype=""><author>Stefan M&uuml;ller</author><title>On Graphs &amp; Networks.</title></article>"#;
+
+        let mut reader = Reader::from_reader(&xml[..]);
+        let mut buf = Vec::new();
+
+        let start = match reader.read_event_into_async(&mut buf).await.unwrap() {
+            Event::Start(start) => start.into_owned(),
+            other => panic!("expected an opening tag, got {other:?}"),
+        };
+
+        let resolved = read_element_async(&mut reader, start, &fixture_entities())
+            .await
+            .unwrap();
+
+        assert!(resolved.contains("Müller"));
+        assert!(!resolved.contains("&uuml;"));
+    }
+}
@@ -2,11 +2,18 @@
 
 mod dataset;
 mod db;
+mod fuzzy;
+mod graph_export;
+mod ingest_async;
+mod migrations;
+mod query_builder;
+#[cfg(test)]
+mod test_support;
 
 use std::{
     collections::HashSet,
     fs,
-    io::Read,
+    io::Write,
     sync::{Arc, Mutex, OnceLock},
 };
 
@@ -31,12 +38,15 @@ pub static DB_CONN_POOL: OnceLock<Pool<SqliteConnectionManager>> = OnceLock::new
 
 /// Initialize the connection pool and get a connection.
 fn get_init_conn_pool() -> PooledConnection<SqliteConnectionManager> {
-    match DB_CONN_POOL.get() {
+    let is_new_pool = DB_CONN_POOL.get().is_none();
+
+    let conn = match DB_CONN_POOL.get() {
         Some(p) => p.get().expect("database path not initialized"),
         None => {
             DB_CONN_POOL.get_or_init(|| {
-                let manager = SqliteConnectionManager::file(
+                let manager = db::connection_manager(
                     DB_PATH.get().expect("database path not initialized"),
+                    db::ConnectionOptions::default(),
                 );
                 // thread pool over all available cores
                 r2d2::Pool::builder()
@@ -52,6 +62,30 @@ fn get_init_conn_pool() -> PooledConnection<SqliteConnectionManager> {
                 .get()
                 .unwrap()
         }
+    };
+
+    db::register_regexp_function(&conn).expect("failed to register regexp() function");
+
+    // the schema only needs upgrading once, right after the pool (and thus
+    // the underlying database file) is first opened
+    if is_new_pool {
+        migrations::run_migrations(&conn).expect("failed to run schema migrations");
+    }
+
+    conn
+}
+
+/// Resolves the `path` argument `init_from_xml`/`update_from_xml` both take:
+/// the given path if any, otherwise `dblp.xml` if present, falling back to
+/// `dblp.xml.gz`.
+fn resolve_xml_path(path: Option<&str>) -> PyResult<&str> {
+    match path {
+        Some(p) => Ok(p),
+        None => match (fs::metadata(XML_GZ_PATH), fs::metadata(XML_PATH)) {
+            (_, Ok(_)) => Ok(XML_PATH),
+            (Ok(_), Err(_)) => Ok(XML_GZ_PATH),
+            (Err(_), Err(_)) => Err(PyTypeError::new_err("No XML file found")),
+        },
     }
 }
 
@@ -62,44 +96,9 @@ fn get_init_conn_pool() -> PooledConnection<SqliteConnectionManager> {
 /// If no file is specified, the default gzipped file `dblp.xml.gz` is used.
 #[pyfunction]
 pub fn init_from_xml(path: Option<String>) -> PyResult<()> {
-    let actual_path = match path.as_deref() {
-        Some(p) => p,
-        None => match (fs::metadata(XML_GZ_PATH), fs::metadata(XML_PATH)) {
-            (_, Ok(_)) => XML_PATH,
-            (Ok(_), Err(_)) => XML_GZ_PATH,
-            (Err(_), Err(_)) => return Err(PyTypeError::new_err("No XML file found")),
-        },
-    };
-
-    let xml_file = fs::read(actual_path).map_err(PyTypeError::new_err)?;
-
-    let xml_data = match actual_path.ends_with(".gz") {
-        true => {
-            println!("Reading gzip file");
+    let actual_path = resolve_xml_path(path.as_deref())?;
 
-            let mut xml_bytes = Vec::new();
-            let mut decoder = flate2::read::GzDecoder::new(xml_file.as_slice());
-            decoder
-                .read_to_end(&mut xml_bytes)
-                .map_err(PyTypeError::new_err)?;
-
-            let raw_xml_str = std::str::from_utf8(&xml_bytes).map_err(PyTypeError::new_err)?;
-            let filtered_xml_str = dataset::strip_references(raw_xml_str);
-
-            // fs::write(XML_DEFAULT_PATH, &filtered_xml_str).map_err(PyTypeError::new_err)?;
-
-            filtered_xml_str
-        }
-        false => {
-            println!("Reading xml file");
-            let raw_xml = std::str::from_utf8(&xml_file).map_err(PyTypeError::new_err)?;
-            let filt_xml = dataset::strip_references(raw_xml);
-
-            filt_xml
-        }
-    };
-
-    drop(xml_file);
+    let reader = dataset::open_xml_stream(actual_path).map_err(PyTypeError::new_err)?;
 
     let mut conn = get_init_conn_pool();
     // rusqlite::Connection::open(DB_DEFAULT_PATH)
@@ -112,7 +111,58 @@ pub fn init_from_xml(path: Option<String>) -> PyResult<()> {
     DB_PATH.get_or_init(|| DB_DEFAULT_PATH.to_string());
 
     println!("writing chunks to: {}", DB_PATH.get().unwrap());
-    db::chunked_deserialize_insert(&mut conn, &xml_data)
+    db::stream_deserialize_insert(&mut conn, reader, dataset::dtd_entities(), 1000)
+        .map_err(|e| PyTypeError::new_err(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Initializes the DBLP database the same way [init_from_xml] does, but
+/// parses `path` and writes to the database concurrently
+/// ([ingest_async::ingest_async]) instead of one record at a time.
+///
+/// `path` must be a gzip-compressed XML file (`*.xml.gz`), since
+/// [ingest_async::ingest_async] always decodes it as a gzip stream itself
+/// rather than going through [dataset::open_xml_stream].
+#[pyfunction]
+pub fn init_from_xml_async(path: String, batch_size: Option<usize>) -> PyResult<()> {
+    DB_PATH.get_or_init(|| DB_DEFAULT_PATH.to_string());
+    let db_path = DB_PATH.get().unwrap();
+
+    println!("writing chunks to: {}", db_path);
+
+    let runtime =
+        tokio::runtime::Runtime::new().map_err(|e| PyTypeError::new_err(e.to_string()))?;
+
+    runtime
+        .block_on(ingest_async::ingest_async(
+            path,
+            db_path,
+            dataset::dtd_entities().clone(),
+            batch_size.unwrap_or(1000),
+        ))
+        .map_err(|e| PyTypeError::new_err(e.to_string()))
+}
+
+/// Incrementally refreshes the DBLP database from a local file, instead of
+/// [init_from_xml]'s `clear_tables` + full reparse.
+///
+/// `path` is resolved the same way as [init_from_xml]. The file must be a
+/// full DBLP dump (not a delta), since any publication or person whose key
+/// isn't present in it is deleted - only publications whose `@mdate` is
+/// newer than what's already stored are actually rewritten, which is what
+/// makes this cheaper than [init_from_xml] for DBLP's frequent releases.
+#[pyfunction]
+pub fn update_from_xml(path: Option<String>) -> PyResult<()> {
+    let actual_path = resolve_xml_path(path.as_deref())?;
+
+    let reader = dataset::open_xml_stream(actual_path).map_err(PyTypeError::new_err)?;
+
+    let mut conn = get_init_conn_pool();
+    DB_PATH.get_or_init(|| DB_DEFAULT_PATH.to_string());
+
+    println!("updating from: {}", DB_PATH.get().unwrap());
+    db::update_from_xml_stream(&mut conn, reader, dataset::dtd_entities(), 1000)
         .map_err(|e| PyTypeError::new_err(e.to_string()))?;
 
     Ok(())
@@ -158,22 +208,69 @@ pub fn query_publications_table(constraints: String) -> PyResult<Vec<DblpRecord>
 
 /// Search for an author in the database.
 ///
-/// If looking for an exact match, set `exact` to `true`.
+/// If looking for an exact match, set `exact` to `true`. Otherwise, set
+/// `fuzzy` to `true` to tolerate typos in `name`, with results ranked by
+/// closeness to the query rather than returned in table order.
 #[pyfunction]
-#[pyo3(signature = (name, exact=false, limit=None))]
-pub fn query_person(name: String, exact: bool, limit: Option<u32>) -> PyResult<Vec<PersonRecord>> {
+#[pyo3(signature = (name, exact=false, fuzzy=false, limit=None))]
+pub fn query_person(
+    name: String,
+    exact: bool,
+    fuzzy: bool,
+    limit: Option<u32>,
+) -> PyResult<Vec<PersonRecord>> {
     let conn = get_init_conn_pool();
+
+    if fuzzy && !exact {
+        return db::query_author_fuzzy(&conn, name, limit)
+            .map_err(|e| PyTypeError::new_err(e.to_string()));
+    }
+
     db::query_author(&conn, name, exact, limit).map_err(|e| PyTypeError::new_err(e.to_string()))
 }
 
 /// Search for a publication in the database.
+///
+/// Set `fuzzy` to `true` to tolerate typos in `title`, with results ranked by
+/// closeness to the query rather than returned in table order.
 #[pyfunction]
-pub fn query_publication(title: String, limit: Option<u32>) -> PyResult<Vec<DblpRecord>> {
+#[pyo3(signature = (title, fuzzy=false, limit=None))]
+pub fn query_publication(
+    title: String,
+    fuzzy: bool,
+    limit: Option<u32>,
+) -> PyResult<Vec<DblpRecord>> {
     let conn = get_init_conn_pool();
 
+    if fuzzy {
+        return db::query_publication_fuzzy(&conn, title, limit)
+            .map_err(|e| PyTypeError::new_err(e.to_string()));
+    }
+
     db::query_publication(&conn, title, limit).map_err(|e| PyTypeError::new_err(e.to_string()))
 }
 
+/// Search for an author using a regular expression pattern, matched against
+/// both their name and their aliases.
+#[pyfunction]
+pub fn query_person_regex(pattern: String, limit: Option<u32>) -> PyResult<Vec<PersonRecord>> {
+    let conn = get_init_conn_pool();
+
+    db::query_author_regex(&conn, pattern, limit).map_err(|e| PyTypeError::new_err(e.to_string()))
+}
+
+/// Search for publications whose authors field matches a regular expression pattern.
+#[pyfunction]
+pub fn query_publication_authors_regex(
+    pattern: String,
+    limit: Option<u32>,
+) -> PyResult<Vec<DblpRecord>> {
+    let conn = get_init_conn_pool();
+
+    db::query_publications_regex(&conn, pattern, limit)
+        .map_err(|e| PyTypeError::new_err(e.to_string()))
+}
+
 /// Search for all publications from a specific author.
 ///
 /// The `limit` parameter can be used to limit the number of results.
@@ -275,6 +372,20 @@ pub fn temporal_relation(
     results
 }
 
+/// Writes every record in `records` as a RIS file at `target`, one record
+/// after another separated by a blank line (the usual RIS multi-record
+/// layout), via [DblpRecord::to_ris].
+#[pyfunction]
+pub fn export_ris(records: Vec<DblpRecord>, target: String) -> PyResult<()> {
+    let mut file = fs::File::create(&target).map_err(|e| PyTypeError::new_err(e.to_string()))?;
+
+    for record in records.iter() {
+        writeln!(file, "{}\n", record.to_ris()).map_err(|e| PyTypeError::new_err(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
 /// Write the temporal relations to a csv file.
 #[pyfunction]
 pub fn save_temporal_relation(
@@ -317,16 +428,25 @@ pub fn save_temporal_relation(
 fn dblp(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(hello_world, m)?)?;
     m.add_function(wrap_pyfunction!(init_from_xml, m)?)?;
+    m.add_function(wrap_pyfunction!(init_from_xml_async, m)?)?;
+    m.add_function(wrap_pyfunction!(update_from_xml, m)?)?;
     m.add_function(wrap_pyfunction!(init_from_sqlite, m)?)?;
     m.add_function(wrap_pyfunction!(query_persons_table, m)?)?;
     m.add_function(wrap_pyfunction!(query_publications_table, m)?)?;
     m.add_function(wrap_pyfunction!(query_person, m)?)?;
+    m.add_function(wrap_pyfunction!(query_person_regex, m)?)?;
+    m.add_function(wrap_pyfunction!(query_publication, m)?)?;
+    m.add_function(wrap_pyfunction!(query_publication_authors_regex, m)?)?;
     m.add_function(wrap_pyfunction!(query_person_publications, m)?)?;
     m.add_function(wrap_pyfunction!(temporal_relation, m)?)?;
     m.add_function(wrap_pyfunction!(save_temporal_relation, m)?)?;
+    m.add_function(wrap_pyfunction!(export_ris, m)?)?;
+    m.add_function(wrap_pyfunction!(graph_export::export_coauthor_graph, m)?)?;
     m.add_class::<dataset::db_items::DblpRecord>()?;
     m.add_class::<dataset::db_items::PersonRecord>()?;
     m.add_class::<dataset::db_items::PublicationRecord>()?;
     m.add_class::<dataset::db_items::PersonTemporalRelation>()?;
+    m.add_class::<query_builder::QueryBuilder>()?;
+    m.add_class::<query_builder::CoauthorQuery>()?;
     Ok(())
 }
@@ -0,0 +1,160 @@
+//! Versioned schema migrations, keyed on SQLite's `PRAGMA user_version`.
+//!
+//! Each entry in [MIGRATIONS] bumps the schema from one version to the next.
+//! [run_migrations] replays every step past the database's on-disk version, so
+//! opening an older `.sqlite` file upgrades it in place instead of silently
+//! breaking against a newer `create_tables`.
+
+use rusqlite::Connection;
+
+/// One migration step: the schema version it brings the database to, and the
+/// SQL statements that perform it.
+pub struct Migration {
+    pub version: u32,
+    pub statements: &'static [&'static str],
+}
+
+/// Ordered migration steps, oldest first.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS persons(
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            profile TEXT NOT NULL,
+            aliases TEXT NOT NULL
+        )",
+            "CREATE TABLE IF NOT EXISTS publications(
+            id INTEGER PRIMARY KEY,
+            record TEXT NOT NULL,
+            key TEXT NOT NULL,
+            mdate TEXT,
+            publtype TEXT,
+            year INTEGER,
+            authors TEXT,
+            citations TEXT,
+            publisher TEXT,
+            school TEXT
+        )",
+        ],
+    },
+    Migration {
+        // token-prefix indexes backing fuzzy search (see crate::fuzzy), kept
+        // up to date row-by-row in db::dump_into_database
+        version: 2,
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS person_name_tokens(
+            token TEXT NOT NULL,
+            person_id INTEGER NOT NULL
+        )",
+            "CREATE INDEX IF NOT EXISTS idx_person_name_tokens_prefix ON person_name_tokens(token)",
+            "CREATE TABLE IF NOT EXISTS publication_key_tokens(
+            token TEXT NOT NULL,
+            publication_id INTEGER NOT NULL
+        )",
+            "CREATE INDEX IF NOT EXISTS idx_publication_key_tokens_prefix ON publication_key_tokens(token)",
+        ],
+    },
+    Migration {
+        // `title`: the `<title>` element, dropped entirely until now.
+        // `authorship`: a real join table linking a publication to each of
+        // its authors by name, alongside the `publications.authors` TEXT
+        // column that string-search callers (query_author_publications,
+        // coauthors, ...) still rely on.
+        version: 3,
+        statements: &[
+            "ALTER TABLE publications ADD COLUMN title TEXT",
+            "CREATE TABLE IF NOT EXISTS authorship(
+            publication_id INTEGER NOT NULL,
+            author_name TEXT NOT NULL
+        )",
+            "CREATE INDEX IF NOT EXISTS idx_authorship_publication_id ON authorship(publication_id)",
+            "CREATE INDEX IF NOT EXISTS idx_authorship_author_name ON authorship(author_name)",
+        ],
+    },
+    Migration {
+        // single-row watermark of the newest publication `mdate` seen by
+        // db::update_from_xml_stream, so incremental updates know how far
+        // they've already ingested.
+        version: 4,
+        statements: &["CREATE TABLE IF NOT EXISTS dataset_watermark(mdate TEXT NOT NULL)"],
+    },
+    Migration {
+        // `person_id`/`year`: turn `authorship` into a real junction table,
+        // so `to_relations` can do an indexed equi-join on `person_id`
+        // instead of a `publications.authors LIKE '%::name::%'` scan
+        // (`coauthors` matches on `publications.authors`, not on this
+        // table). `person_id` is nullable - not every author name in
+        // `authorship` has a corresponding `persons` row (only those with a
+        // DBLP home page do).
+        version: 5,
+        statements: &[
+            "ALTER TABLE authorship ADD COLUMN person_id INTEGER",
+            "ALTER TABLE authorship ADD COLUMN year INTEGER",
+            "CREATE INDEX IF NOT EXISTS idx_authorship_person_id_year ON authorship(person_id, year)",
+        ],
+    },
+    Migration {
+        // a token-prefix index over `publications.title` (falling back to
+        // `key` for records with no title), kept up to date row-by-row in
+        // db::insert_publication_row, so query_publication_fuzzy can narrow
+        // + rank candidates against the title instead of the key.
+        version: 6,
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS publication_title_tokens(
+            token TEXT NOT NULL,
+            publication_id INTEGER NOT NULL
+        )",
+            "CREATE INDEX IF NOT EXISTS idx_publication_title_tokens_prefix ON publication_title_tokens(token)",
+        ],
+    },
+];
+
+/// The latest schema version this build of the crate knows about.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.iter().map(|m| m.version).max().unwrap_or(0)
+}
+
+/// The on-disk schema version of a database, alongside the latest version
+/// this build knows about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaVersion {
+    pub on_disk: u32,
+    pub latest: u32,
+}
+
+/// Reads `conn`'s `PRAGMA user_version` and pairs it with [latest_version].
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<SchemaVersion> {
+    let on_disk: u32 = conn.query_row("PRAGMA user_version", (), |r| r.get(0))?;
+
+    Ok(SchemaVersion {
+        on_disk,
+        latest: latest_version(),
+    })
+}
+
+/// Applies every migration step with a version greater than `conn`'s current
+/// `user_version`, each inside its own transaction, bumping `user_version` as
+/// it goes. A freshly created (empty) database starts at version `0`, so it
+/// replays every migration in order.
+pub fn run_migrations(conn: &Connection) -> rusqlite::Result<()> {
+    let current: u32 = conn.query_row("PRAGMA user_version", (), |r| r.get(0))?;
+
+    let mut pending = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current)
+        .collect::<Vec<_>>();
+    pending.sort_by_key(|m| m.version);
+
+    for migration in pending {
+        conn.execute_batch(&format!(
+            "BEGIN;\n{};\nCOMMIT;",
+            migration.statements.join(";\n")
+        ))?;
+
+        conn.pragma_update(None, "user_version", migration.version)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,462 @@
+//! A small, injection-safe query-algebra layer over the `persons` and
+//! `publications` tables, exposed to Python as [QueryBuilder].
+//!
+//! Predicates are built up as an AST instead of splicing strings together
+//! (as `query_persons_table`/`query_publications_table` do), validated
+//! against a fixed column allowlist per table, and only lowered to a
+//! parameterized statement at the very end - so values are always bound,
+//! never interpolated.
+
+use pyo3::{exceptions::PyValueError, pyclass, pymethods, FromPyObject, PyResult};
+use rusqlite::types::Value;
+
+use crate::{
+    dataset::db_items::{DblpRecord, PersonRecord, PublicationRecord, SEPARATOR},
+    db, get_init_conn_pool,
+};
+
+const PERSON_COLUMNS: &[&str] = &["id", "name", "profile", "aliases"];
+const PUBLICATION_COLUMNS: &[&str] = &[
+    "id",
+    "record",
+    "key",
+    "mdate",
+    "publtype",
+    "year",
+    "authors",
+    "citations",
+    "publisher",
+    "school",
+    "title",
+];
+
+const ALLOWED_OPS: &[&str] = &["=", "!=", "<", "<=", ">", ">=", "LIKE"];
+
+/// Which table a [QueryBuilder] targets, fixing its column allowlist.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QueryTarget {
+    Persons,
+    Publications,
+}
+
+impl QueryTarget {
+    fn table_name(self) -> &'static str {
+        match self {
+            QueryTarget::Persons => "persons",
+            QueryTarget::Publications => "publications",
+        }
+    }
+
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            QueryTarget::Persons => PERSON_COLUMNS,
+            QueryTarget::Publications => PUBLICATION_COLUMNS,
+        }
+    }
+}
+
+/// A scalar value accepted from Python, for `filter`/`in_` comparisons.
+#[derive(Clone, Debug, FromPyObject)]
+pub enum QueryValue {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl From<QueryValue> for Value {
+    fn from(value: QueryValue) -> Self {
+        match value {
+            QueryValue::Int(i) => Value::Integer(i),
+            QueryValue::Float(f) => Value::Real(f),
+            QueryValue::Text(s) => Value::Text(s),
+        }
+    }
+}
+
+/// One node of the predicate AST built up by [QueryBuilder].
+#[derive(Clone, Debug)]
+enum Predicate {
+    Compare {
+        column: String,
+        op: &'static str,
+        value: Value,
+    },
+    In {
+        column: String,
+        values: Vec<Value>,
+    },
+    YearBetween {
+        start: u32,
+        end: u32,
+    },
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+/// How a new predicate is folded into the builder's existing one.
+#[derive(Clone, Copy)]
+enum Combinator {
+    And,
+    Or,
+}
+
+/// Builds an AST of predicates over the `persons`/`publications` tables and
+/// lowers it to a parameterized statement, instead of the free-form
+/// `constraints: String` that `query_persons_table`/`query_publications_table`
+/// splice directly into SQL.
+///
+/// Construct one with [QueryBuilder::persons] or [QueryBuilder::publications],
+/// chain `filter`/`and_`/`or_`/`in_`/`year_between`/`order_by`/`limit`, then
+/// call `execute_persons`/`execute_publications` to run it.
+#[pyclass]
+#[derive(Clone)]
+pub struct QueryBuilder {
+    target: QueryTarget,
+    predicate: Option<Predicate>,
+    order_by: Option<(String, bool)>,
+    limit: Option<u32>,
+}
+
+#[pymethods]
+impl QueryBuilder {
+    /// Starts a query over the `persons` table.
+    #[staticmethod]
+    pub fn persons() -> Self {
+        Self {
+            target: QueryTarget::Persons,
+            predicate: None,
+            order_by: None,
+            limit: None,
+        }
+    }
+
+    /// Starts a query over the `publications` table.
+    #[staticmethod]
+    pub fn publications() -> Self {
+        Self {
+            target: QueryTarget::Publications,
+            predicate: None,
+            order_by: None,
+            limit: None,
+        }
+    }
+
+    /// Adds `column <op> value`, combined with any existing predicate via
+    /// `AND`. An alias for [QueryBuilder::and_].
+    pub fn filter(&self, column: String, op: String, value: QueryValue) -> PyResult<Self> {
+        self.and_(column, op, value)
+    }
+
+    /// Adds `column <op> value`, combined with any existing predicate via
+    /// `AND`. `op` is one of `=`, `!=`, `<`, `<=`, `>`, `>=`, `LIKE`.
+    pub fn and_(&self, column: String, op: String, value: QueryValue) -> PyResult<Self> {
+        let predicate = self.compare(column, op, value)?;
+        Ok(self.combine(predicate, Combinator::And))
+    }
+
+    /// Adds `column <op> value`, combined with any existing predicate via
+    /// `OR`.
+    pub fn or_(&self, column: String, op: String, value: QueryValue) -> PyResult<Self> {
+        let predicate = self.compare(column, op, value)?;
+        Ok(self.combine(predicate, Combinator::Or))
+    }
+
+    /// Adds `column IN (values)`, combined with any existing predicate via
+    /// `AND`.
+    pub fn in_(&self, column: String, values: Vec<QueryValue>) -> PyResult<Self> {
+        let column = self.validate_column(&column)?;
+        let predicate = Predicate::In {
+            column,
+            values: values.into_iter().map(QueryValue::into).collect(),
+        };
+
+        Ok(self.combine(predicate, Combinator::And))
+    }
+
+    /// Adds `year BETWEEN start AND end`, combined with any existing
+    /// predicate via `AND`. Only valid for [QueryBuilder::publications].
+    pub fn year_between(&self, start: u32, end: u32) -> PyResult<Self> {
+        if !self.target.columns().contains(&"year") {
+            return Err(PyValueError::new_err(format!(
+                "table `{}` has no `year` column",
+                self.target.table_name()
+            )));
+        }
+
+        Ok(self.combine(Predicate::YearBetween { start, end }, Combinator::And))
+    }
+
+    /// Orders results by `column`, ascending unless `descending` is set.
+    #[pyo3(signature = (column, descending=false))]
+    pub fn order_by(&self, column: String, descending: bool) -> PyResult<Self> {
+        let column = self.validate_column(&column)?;
+        let mut next = self.clone();
+        next.order_by = Some((column, !descending));
+
+        Ok(next)
+    }
+
+    /// Caps the number of returned rows.
+    pub fn limit(&self, limit: u32) -> Self {
+        let mut next = self.clone();
+        next.limit = Some(limit);
+
+        next
+    }
+
+    /// Runs the builder against the `persons` table.
+    ///
+    /// Fails if this builder was started with [QueryBuilder::publications].
+    pub fn execute_persons(&self) -> PyResult<Vec<PersonRecord>> {
+        self.require_target(QueryTarget::Persons)?;
+
+        let conn = get_init_conn_pool();
+        db::query_persons_builder(&conn, self)
+            .map_err(|e| pyo3::exceptions::PyTypeError::new_err(e.to_string()))
+    }
+
+    /// Runs the builder against the `publications` table.
+    ///
+    /// Fails if this builder was started with [QueryBuilder::persons].
+    pub fn execute_publications(&self) -> PyResult<Vec<DblpRecord>> {
+        self.require_target(QueryTarget::Publications)?;
+
+        let conn = get_init_conn_pool();
+        db::query_publications_builder(&conn, self)
+            .map_err(|e| pyo3::exceptions::PyTypeError::new_err(e.to_string()))
+    }
+}
+
+impl QueryBuilder {
+    fn require_target(&self, expected: QueryTarget) -> PyResult<()> {
+        if self.target != expected {
+            return Err(PyValueError::new_err(format!(
+                "this query builder targets `{}`, not `{}`",
+                self.target.table_name(),
+                expected.table_name()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn compare(&self, column: String, op: String, value: QueryValue) -> PyResult<Predicate> {
+        Ok(Predicate::Compare {
+            column: self.validate_column(&column)?,
+            op: validate_op(&op)?,
+            value: value.into(),
+        })
+    }
+
+    fn validate_column(&self, column: &str) -> PyResult<String> {
+        if self.target.columns().contains(&column) {
+            Ok(column.to_string())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "unknown column `{}` for table `{}`",
+                column,
+                self.target.table_name()
+            )))
+        }
+    }
+
+    fn combine(&self, predicate: Predicate, combinator: Combinator) -> Self {
+        let mut next = self.clone();
+
+        next.predicate = Some(match next.predicate.take() {
+            Some(existing) => match combinator {
+                Combinator::And => Predicate::And(Box::new(existing), Box::new(predicate)),
+                Combinator::Or => Predicate::Or(Box::new(existing), Box::new(predicate)),
+            },
+            None => predicate,
+        });
+
+        next
+    }
+
+    /// Lowers the builder into a parameterized `SELECT` statement and its
+    /// bound values, in positional order. `LIMIT` is always bound (`-1`
+    /// meaning unlimited), matching the cache-friendly convention used by
+    /// [db::query_author]/[db::query_author_publications].
+    pub(crate) fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut params = Vec::new();
+        let mut sql = format!("SELECT * FROM {}", self.target.table_name());
+
+        if let Some(predicate) = &self.predicate {
+            sql.push_str(" WHERE ");
+            sql.push_str(&render_predicate(predicate, &mut params));
+        }
+
+        if let Some((column, ascending)) = &self.order_by {
+            sql.push_str(&format!(
+                " ORDER BY {} {}",
+                column,
+                if *ascending { "ASC" } else { "DESC" }
+            ));
+        }
+
+        sql.push_str(" LIMIT ?");
+        params.push(Value::Integer(self.limit.map(|l| l as i64).unwrap_or(-1)));
+
+        (sql, params)
+    }
+}
+
+/// Builds the coauthor-matching query used by [PersonRecord::coauthors] and
+/// [PersonRecord::to_relations], replacing the `format!("... LIKE '%::{}::%'"
+/// , name)` string splicing those used to do directly against an author name.
+///
+/// Construct with [CoauthorQuery::new], chain `author`/`author_id`/
+/// `year_range`/`publication_types`, then call `execute` to run it.
+#[pyclass]
+#[derive(Clone, Debug, Default)]
+pub struct CoauthorQuery {
+    author: Option<String>,
+    author_id: Option<u32>,
+    year_range: Option<(u32, u32)>,
+    publication_types: Option<Vec<PublicationRecord>>,
+}
+
+#[pymethods]
+impl CoauthorQuery {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts to publications whose `::`-joined `authors` column contains
+    /// `name`, bound as a parameter rather than spliced into the SQL text.
+    pub fn author(&self, name: String) -> Self {
+        let mut next = self.clone();
+        next.author = Some(name);
+
+        next
+    }
+
+    /// Restricts to publications authored by the person with this
+    /// `persons.id`, via an indexed equi-join on `authorship.person_id`
+    /// instead of an `authors LIKE` scan. Takes priority over [Self::author]
+    /// when both are set.
+    pub fn author_id(&self, id: u32) -> Self {
+        let mut next = self.clone();
+        next.author_id = Some(id);
+
+        next
+    }
+
+    /// Restricts to publications published between `start` and `end`,
+    /// inclusive.
+    pub fn year_range(&self, start: u32, end: u32) -> Self {
+        let mut next = self.clone();
+        next.year_range = Some((start, end));
+
+        next
+    }
+
+    /// Restricts to publications whose `record` type is one of `types`.
+    pub fn publication_types(&self, types: Vec<PublicationRecord>) -> Self {
+        let mut next = self.clone();
+        next.publication_types = Some(types);
+
+        next
+    }
+
+    /// Runs the query, returning each matching publication's `(year,
+    /// authors)` pair.
+    pub fn execute(&self) -> PyResult<Vec<(Option<u32>, Option<String>)>> {
+        let conn = get_init_conn_pool();
+        db::query_coauthors(&conn, self).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+impl CoauthorQuery {
+    /// Lowers the builder into a parameterized `SELECT publications.year,
+    /// publications.authors` statement and its bound values, in positional
+    /// order. When [Self::author_id] is set, the query joins through
+    /// `authorship` for an indexed equi-join on `person_id` (and filters
+    /// `year_range` against `authorship.year`, populated at ingest time)
+    /// instead of scanning `publications.authors`.
+    pub(crate) fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut clauses = Vec::new();
+        let mut params = Vec::new();
+
+        let mut sql = if self.author_id.is_some() {
+            "SELECT publications.year, publications.authors FROM authorship \
+             JOIN publications ON publications.id = authorship.publication_id"
+                .to_string()
+        } else {
+            "SELECT publications.year, publications.authors FROM publications".to_string()
+        };
+
+        if let Some(person_id) = self.author_id {
+            clauses.push("authorship.person_id = ?".to_string());
+            params.push(Value::Integer(person_id as i64));
+        }
+
+        if let Some(author) = self.author.as_ref().filter(|_| self.author_id.is_none()) {
+            clauses.push("publications.authors LIKE ?".to_string());
+            params.push(Value::Text(format!("%{SEPARATOR}{author}{SEPARATOR}%")));
+        }
+
+        if let Some((start, end)) = self.year_range {
+            let year_column = if self.author_id.is_some() {
+                "authorship.year"
+            } else {
+                "publications.year"
+            };
+            clauses.push(format!("{year_column} BETWEEN ? AND ?"));
+            params.push(Value::Integer(start as i64));
+            params.push(Value::Integer(end as i64));
+        }
+
+        if let Some(types) = &self.publication_types {
+            let placeholders = types.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+            clauses.push(format!("publications.record IN ({})", placeholders));
+            params.extend(types.iter().map(|t| Value::Text(t.to_string())));
+        }
+
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&clauses.join(" AND "));
+        }
+
+        (sql, params)
+    }
+}
+
+fn validate_op(op: &str) -> PyResult<&'static str> {
+    ALLOWED_OPS
+        .iter()
+        .find(|allowed| allowed.eq_ignore_ascii_case(op))
+        .copied()
+        .ok_or_else(|| PyValueError::new_err(format!("unsupported comparison operator `{}`", op)))
+}
+
+fn render_predicate(predicate: &Predicate, params: &mut Vec<Value>) -> String {
+    match predicate {
+        Predicate::Compare { column, op, value } => {
+            params.push(value.clone());
+            format!("{} {} ?", column, op)
+        }
+        Predicate::In { column, values } => {
+            let placeholders = values.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+            params.extend(values.iter().cloned());
+            format!("{} IN ({})", column, placeholders)
+        }
+        Predicate::YearBetween { start, end } => {
+            params.push(Value::Integer(*start as i64));
+            params.push(Value::Integer(*end as i64));
+            "year BETWEEN ? AND ?".to_string()
+        }
+        Predicate::And(left, right) => format!(
+            "({}) AND ({})",
+            render_predicate(left, params),
+            render_predicate(right, params)
+        ),
+        Predicate::Or(left, right) => format!(
+            "({}) OR ({})",
+            render_predicate(left, params),
+            render_predicate(right, params)
+        ),
+    }
+}
@@ -0,0 +1,49 @@
+//! Synthetic DBLP XML fixtures and sqlite tempfile helpers, so the parser and
+//! ingestion tests are hermetic and don't need a local multi-GB dump.
+#![cfg(test)]
+
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+/// A small DBLP-shaped XML document covering the tricky cases a real dump
+/// throws at the parser: entity references, nested tags, a self-closing
+/// element, CDATA, and an attribute value containing `>`.
+pub(crate) fn sample_dblp_xml() -> &'static str {
+    r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<!DOCTYPE dblp SYSTEM "dblp.dtd">
+<dblp>
+<article mdate="2023-01-01" key="journals/test/Mueller23" publtype="">
+<author>Stefan M&uuml;ller</author>
+<title>On Graphs &amp; Networks.</title>
+<year>2023</year>
+<journal>Test Journal</journal>
+<ee>https://doi.org/10.1000/test</ee>
+</article>
+<inproceedings mdate="2022-06-15" key="conf/test/Doe22" publtype="informal" weird="a &gt; b">
+<author>Jane Doe</author>
+<author orcid="0000-0000-0000-0001">John Q. Public 0001</author>
+<title>A Study of Things<![CDATA[ & Other <Stuff> ]]></title>
+<year>2022</year>
+<booktitle>TEST 2022</booktitle>
+<ee self-closing="true"/>
+<crossref>conf/test/2022</crossref>
+</inproceedings>
+<www key="homepages/m/StefanMueller" publtype="">
+<title>Home Page</title>
+<author>Stefan M&uuml;ller</author>
+<author>S. Mueller</author>
+</www>
+</dblp>
+"#
+}
+
+/// Creates a fresh sqlite path inside a new [TempDir]. Keep the returned
+/// `TempDir` alive for as long as the database is in use - the directory
+/// (and the database file inside it) is removed once it drops.
+pub(crate) fn temp_sqlite_path() -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let path = dir.path().join("test.sqlite");
+
+    (dir, path)
+}